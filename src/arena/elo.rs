@@ -0,0 +1,342 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::Summary;
+
+/// Rating given to a variant the first time it plays.
+pub const INITIAL_RATING: f64 = 1500.0;
+
+/// How far a single game can move a rating.
+const K: f64 = 32.0;
+
+/// z-score of the two-sided 95% confidence interval.
+const Z: f64 = 1.96;
+
+/// A variant's rating and the results it's built on.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Record {
+    pub rating: f64,
+    pub games: usize,
+    pub wins: usize,
+    /// Head to head comparisons against every other snake in each game played.
+    pub matchups: usize,
+    /// Points taken from those matchups: one for outlasting the other snake, half for going out
+    /// on the same turn or both surviving.
+    pub points: f64,
+}
+
+impl Default for Record {
+    fn default() -> Self {
+        Record {
+            rating: INITIAL_RATING,
+            games: 0,
+            wins: 0,
+            matchups: 0,
+            points: 0.0,
+        }
+    }
+}
+
+impl Record {
+    /// The share of available matchup points taken.
+    pub fn score(&self) -> Option<f64> {
+        if self.matchups == 0 {
+            None
+        } else {
+            Some(self.points / self.matchups as f64)
+        }
+    }
+
+    /// Half the width of the 95% confidence interval on the rating, from the normal
+    /// approximation of the matchup score mapped onto the Elo scale.
+    pub fn margin(&self) -> Option<f64> {
+        let score = self.score()?.clamp(0.01, 0.99);
+        let error = Z * (score * (1.0 - score) / self.matchups as f64).sqrt();
+
+        let low = elo_difference((score - error).clamp(0.01, 0.99));
+        let high = elo_difference((score + error).clamp(0.01, 0.99));
+
+        Some((high - low) / 2.0)
+    }
+}
+
+/// Elo ratings for every variant that's played, keyed by variant name.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Ratings {
+    pub records: BTreeMap<String, Record>,
+}
+
+/// One row of the leaderboard.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub rank: usize,
+    pub name: String,
+    pub rating: f64,
+    pub low: Option<f64>,
+    pub high: Option<f64>,
+    pub games: usize,
+    pub wins: usize,
+    pub score: Option<f64>,
+}
+
+impl Ratings {
+    /// Loads ratings from a JSON file, starting afresh if it doesn't exist yet.
+    pub fn load(path: &Path) -> Result<Ratings> {
+        if !path.exists() {
+            return Ok(Ratings::default());
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read ratings {}", path.display()))?;
+
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write ratings {}", path.display()))
+    }
+
+    pub fn get(&self, name: &str) -> Record {
+        self.records.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Updates ratings from a finished game, keyed by each snake's strategy name. Every pair of
+    /// snakes from different variants counts as a match won by whoever lasted longer, and all of a
+    /// game's rating changes are worked out from the ratings before it. A variant seated more than
+    /// once still plays one game, its rating moving by the average over all its matches.
+    pub fn record(&mut self, summary: &Summary) {
+        let seats = summary.snakes.len();
+        if seats < 2 {
+            return;
+        }
+
+        // Survivors outlast everyone.
+        let lasted: Vec<u32> = summary
+            .snakes
+            .iter()
+            .map(|outcome| match &outcome.death {
+                Some((turn, _)) => *turn as u32,
+                None => u32::MAX,
+            })
+            .collect();
+
+        // Per variant: the sum of points over expected, matchups, points and whether it won.
+        let mut tallies: BTreeMap<&str, (f64, usize, f64, bool)> = BTreeMap::new();
+        for (a, outcome) in summary.snakes.iter().enumerate() {
+            let tally = tallies.entry(&outcome.strategy).or_default();
+            tally.3 |= summary.winner.as_ref() == Some(&outcome.id);
+
+            for (b, other) in summary.snakes.iter().enumerate() {
+                if outcome.strategy == other.strategy {
+                    continue;
+                }
+
+                let points = match lasted[a].cmp(&lasted[b]) {
+                    std::cmp::Ordering::Greater => 1.0,
+                    std::cmp::Ordering::Equal => 0.5,
+                    std::cmp::Ordering::Less => 0.0,
+                };
+                let expected = expected(
+                    self.get(&outcome.strategy).rating,
+                    self.get(&other.strategy).rating,
+                );
+
+                tally.0 += points - expected;
+                tally.1 += 1;
+                tally.2 += points;
+            }
+        }
+
+        for (name, (surplus, matchups, points, won)) in tallies {
+            let record = self.records.entry(name.to_string()).or_default();
+
+            if matchups > 0 {
+                record.rating += K * surplus / matchups as f64;
+            }
+            record.games += 1;
+            record.matchups += matchups;
+            record.points += points;
+            if won {
+                record.wins += 1;
+            }
+        }
+    }
+
+    /// Every variant, best rated first.
+    pub fn leaderboard(&self) -> Vec<Standing> {
+        let mut standings: Vec<Standing> = self
+            .records
+            .iter()
+            .map(|(name, record)| {
+                let margin = record.margin();
+
+                Standing {
+                    rank: 0,
+                    name: name.clone(),
+                    rating: record.rating,
+                    low: margin.map(|margin| record.rating - margin),
+                    high: margin.map(|margin| record.rating + margin),
+                    games: record.games,
+                    wins: record.wins,
+                    score: record.score(),
+                }
+            })
+            .collect();
+
+        standings.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+        for (index, standing) in standings.iter_mut().enumerate() {
+            standing.rank = index + 1;
+        }
+
+        standings
+    }
+}
+
+/// Writes the leaderboard as JSON if the path ends in `.json`, otherwise as CSV.
+pub fn write_leaderboard(standings: &[Standing], path: &Path) -> Result<()> {
+    let contents = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => serde_json::to_string_pretty(standings)?,
+        _ => to_csv(standings),
+    };
+
+    std::fs::write(path, contents)
+        .with_context(|| format!("failed to write leaderboard {}", path.display()))
+}
+
+fn to_csv(standings: &[Standing]) -> String {
+    let optional = |value: Option<f64>, precision: usize| {
+        value
+            .map(|value| format!("{:.*}", precision, value))
+            .unwrap_or_default()
+    };
+
+    let mut csv = String::from("rank,name,rating,low,high,games,wins,score\n");
+    for standing in standings {
+        csv.push_str(&format!(
+            "{},\"{}\",{:.1},{},{},{},{},{}\n",
+            standing.rank,
+            standing.name.replace('"', "\"\""),
+            standing.rating,
+            optional(standing.low, 1),
+            optional(standing.high, 1),
+            standing.games,
+            standing.wins,
+            optional(standing.score, 3),
+        ));
+    }
+
+    csv
+}
+
+/// The expected matchup score of a player rated `a` against one rated `b`.
+fn expected(a: f64, b: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((b - a) / 400.0))
+}
+
+/// The rating difference that gives an expected score of `score`.
+fn elo_difference(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::arena::Outcome;
+    use crate::game::{Elimination, EliminationCause};
+
+    use super::*;
+
+    fn outcome(strategy: &str, death: Option<u16>) -> Outcome {
+        Outcome {
+            id: format!("{}-0", strategy),
+            strategy: strategy.to_string(),
+            length: 3,
+            death: death.map(|turn| {
+                (
+                    turn,
+                    Elimination {
+                        id: format!("{}-0", strategy),
+                        cause: EliminationCause::OutOfBounds,
+                        by: None,
+                    },
+                )
+            }),
+        }
+    }
+
+    #[test]
+    fn record() {
+        let mut ratings = Ratings::default();
+        ratings.record(&Summary {
+            game: "asdf".to_string(),
+            ruleset: "standard".to_string(),
+            turns: 20,
+            winner: Some("a-0".to_string()),
+            snakes: vec![
+                outcome("a", None),
+                outcome("b", Some(10)),
+                outcome("c", Some(10)),
+            ],
+        });
+
+        let (a, b, c) = (ratings.get("a"), ratings.get("b"), ratings.get("c"));
+        assert_eq!(a.rating, INITIAL_RATING + 16.0);
+        assert_eq!(b.rating, INITIAL_RATING - 8.0);
+        assert_eq!(b, c);
+        assert_eq!((a.games, a.wins, a.matchups, a.points), (1, 1, 2, 2.0));
+        assert_eq!(b.score(), Some(0.25));
+
+        let leaderboard = ratings.leaderboard();
+        assert_eq!(leaderboard[0].name, "a");
+        assert_eq!(leaderboard[0].rank, 1);
+        assert!(leaderboard[0].low < Some(a.rating) && leaderboard[0].high > Some(a.rating));
+        assert!(to_csv(&leaderboard)
+            .starts_with("rank,name,rating,low,high,games,wins,score\n1,\"a\",1516.0,"));
+    }
+
+    #[test]
+    fn seated_twice() {
+        let mut ratings = Ratings::default();
+        let mut second = outcome("a", Some(5));
+        second.id = "a-1".to_string();
+        ratings.record(&Summary {
+            game: "asdf".to_string(),
+            ruleset: "standard".to_string(),
+            turns: 20,
+            winner: Some("a-0".to_string()),
+            snakes: vec![
+                outcome("a", None),
+                second,
+                outcome("b", Some(10)),
+                outcome("c", Some(10)),
+            ],
+        });
+
+        let (a, b) = (ratings.get("a"), ratings.get("b"));
+        assert_eq!((a.games, a.wins, a.matchups, a.points), (1, 1, 4, 2.0));
+        assert_eq!(a.rating, INITIAL_RATING);
+        // A loss to the surviving `a`, a win over the other, and a draw with `c`.
+        assert_eq!((b.games, b.wins, b.matchups, b.points), (1, 0, 3, 1.5));
+        assert_eq!(b.rating, INITIAL_RATING);
+    }
+
+    #[test]
+    fn margin_narrows() {
+        let few = Record {
+            matchups: 10,
+            points: 5.0,
+            ..Record::default()
+        };
+        let many = Record {
+            matchups: 1000,
+            points: 500.0,
+            ..Record::default()
+        };
+
+        assert!(many.margin() < few.margin());
+        assert_eq!(Record::default().margin(), None);
+    }
+}
@@ -0,0 +1,404 @@
+mod elo;
+mod tournament;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::game::{Board, Elimination, Game, Mode, Move, Point, Ruleset, Snake, State, MAX_HEALTH};
+use crate::search::Deadline;
+use crate::session::Session;
+use crate::strategy::{self, Strategy};
+
+pub use elo::{write_leaderboard, Ratings, Record, Standing, INITIAL_RATING};
+pub use tournament::{Setup, Tournament, Variant};
+
+/// Length every snake starts the game with, stacked on its starting cell.
+const START_LENGTH: usize = 3;
+
+/// Runs whole games locally, with every snake played in-process by one of our strategies.
+pub struct Arena {
+    pub ruleset: Ruleset,
+    pub width: i16,
+    pub height: i16,
+    pub timeout: u64,
+    /// Games still going after this many turns are called a draw.
+    pub max_turns: u16,
+    pub snakes: Vec<Arc<dyn Strategy>>,
+}
+
+/// How one snake fared in a game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub id: String,
+    pub strategy: String,
+    pub length: usize,
+    /// The turn the snake was eliminated on and why, if it was.
+    pub death: Option<(u16, Elimination)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Summary {
+    pub game: String,
+    pub ruleset: String,
+    pub turns: u16,
+    pub winner: Option<String>,
+    pub snakes: Vec<Outcome>,
+}
+
+impl Arena {
+    /// Plays a game to the end, using `rng` for starting positions and food and hazard spawns.
+    pub fn play<R: Rng>(&self, id: &str, rng: &mut R) -> Summary {
+        let mut state = self.setup(id, rng);
+        let strategies: HashMap<String, &Arc<dyn Strategy>> = state
+            .board
+            .snakes
+            .iter()
+            .map(|snake| snake.id.clone())
+            .zip(self.snakes.iter())
+            .collect();
+
+        let mut outcomes: Vec<Outcome> = state
+            .board
+            .snakes
+            .iter()
+            .map(|snake| Outcome {
+                id: snake.id.clone(),
+                strategy: strategies[&snake.id].name().to_string(),
+                length: snake.length(),
+                death: None,
+            })
+            .collect();
+
+        let mut sessions: HashMap<String, Session> = HashMap::new();
+        // The last turn each snake played, to wrap up with once the game is over.
+        let mut last: HashMap<String, State> = HashMap::new();
+        for view in views(&state) {
+            let mut session = Session::new(&view);
+            strategies[&view.you.id].start(&view, &mut session);
+            sessions.insert(view.you.id.clone(), session);
+        }
+
+        while !state.is_over() && state.turn < self.max_turns {
+            let moves: HashMap<String, Move> = views(&state)
+                .into_iter()
+                .map(|view| {
+                    let session = sessions.get_mut(&view.you.id).expect("started every snake");
+                    let strategy = strategies[&view.you.id];
+
+                    // As the server does, a strategy that fails or panics plays the safe move.
+                    let decision = panic::catch_unwind(AssertUnwindSafe(|| {
+                        strategy.decide(&view, session, Deadline::for_game(&view.game))
+                    }));
+                    let mv = match decision {
+                        Ok(Ok(decision)) => decision.mv,
+                        _ => strategy::safe_move(&view),
+                    };
+
+                    let id = view.you.id.clone();
+                    last.insert(id.clone(), view);
+                    (id, mv)
+                })
+                .collect();
+
+            let (mut next, eliminations) = state.advance(&moves);
+            for elimination in eliminations {
+                if let Some(outcome) = outcomes.iter_mut().find(|o| o.id == elimination.id) {
+                    outcome.death = Some((next.turn, elimination));
+                }
+            }
+
+            next.spawn(rng);
+            state = next;
+
+            for snake in state.board.snakes.iter() {
+                if let Some(outcome) = outcomes.iter_mut().find(|o| o.id == snake.id) {
+                    outcome.length = snake.length();
+                }
+            }
+        }
+
+        // Every snake is wrapped up, eliminated or not, as it would be by the engine's `/end`.
+        for view in views(&state) {
+            last.insert(view.you.id.clone(), view);
+        }
+        for outcome in outcomes.iter() {
+            if let (Some(view), Some(session)) =
+                (last.get(&outcome.id), sessions.get_mut(&outcome.id))
+            {
+                strategies[&outcome.id].end(view, session);
+            }
+        }
+
+        let winner = match state.board.snakes.as_slice() {
+            [winner] if outcomes.len() > 1 => Some(winner.id.clone()),
+            _ => None,
+        };
+
+        Summary {
+            game: id.to_string(),
+            ruleset: self.ruleset.name.clone(),
+            turns: state.turn,
+            winner,
+            snakes: outcomes,
+        }
+    }
+
+    /// Places the snakes at random starting spots, with a piece of food next to each and one in
+    /// the middle.
+    pub fn setup<R: Rng>(&self, id: &str, rng: &mut R) -> State {
+        let mut starts = self.starts(rng);
+        starts.truncate(self.snakes.len());
+
+        let snakes: Vec<Snake> = self
+            .snakes
+            .iter()
+            .zip(starts.iter())
+            .enumerate()
+            .map(|(index, (strategy, start))| Snake {
+                id: format!("{}-{}", strategy.name(), index),
+                health: MAX_HEALTH,
+                head: *start,
+                body: vec![*start; START_LENGTH],
+                ..Default::default()
+            })
+            .collect();
+
+        let mut board = Board {
+            height: self.height,
+            width: self.width,
+            food: vec![],
+            hazards: vec![],
+            snakes,
+            wrapped: false,
+        };
+
+        for start in starts.iter() {
+            let free: Vec<Point> = [(-1, -1), (-1, 1), (1, -1), (1, 1)]
+                .iter()
+                .map(|(x, y)| Point::new(start.x + x, start.y + y))
+                .filter(|point| board.in_bounds(point) && !board.food_at(point))
+                .collect();
+
+            if let Some(food) = free.choose(rng) {
+                board.food.push(*food);
+            }
+        }
+
+        let center = Point::new(self.width / 2, self.height / 2);
+        if board.snake_at(&center).is_none() && !board.food_at(&center) {
+            board.food.push(center);
+        }
+
+        let game = Game {
+            id: id.to_string(),
+            ruleset: self.ruleset.clone(),
+            timeout: self.timeout,
+            ..Default::default()
+        };
+        board.wrapped = game.ruleset.mode() == Mode::Wrapped;
+
+        State {
+            game,
+            turn: 0,
+            you: board.snakes.first().cloned().unwrap_or_default(),
+            board,
+        }
+    }
+
+    /// Starting spots one cell in from the corners first, then from the middle of each edge, in a
+    /// random order within each group.
+    fn starts<R: Rng>(&self, rng: &mut R) -> Vec<Point> {
+        let (left, right) = (1.min(self.width - 1), (self.width - 2).max(0));
+        let (bottom, top) = (1.min(self.height - 1), (self.height - 2).max(0));
+        let (middle_x, middle_y) = (self.width / 2, self.height / 2);
+
+        let mut corners = vec![
+            Point::new(left, bottom),
+            Point::new(left, top),
+            Point::new(right, bottom),
+            Point::new(right, top),
+        ];
+        let mut edges = vec![
+            Point::new(left, middle_y),
+            Point::new(middle_x, bottom),
+            Point::new(right, middle_y),
+            Point::new(middle_x, top),
+        ];
+
+        corners.shuffle(rng);
+        edges.shuffle(rng);
+
+        let mut starts: Vec<Point> = Vec::new();
+        for point in corners.into_iter().chain(edges) {
+            if !starts.contains(&point) {
+                starts.push(point);
+            }
+        }
+
+        starts
+    }
+}
+
+/// The state as each living snake sees it.
+fn views(state: &State) -> Vec<State> {
+    state
+        .board
+        .snakes
+        .iter()
+        .map(|snake| State {
+            you: snake.clone(),
+            ..state.clone()
+        })
+        .collect()
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.winner {
+            Some(winner) => writeln!(
+                f,
+                "game {} ({}): {} won after {} turns",
+                self.game, self.ruleset, winner, self.turns
+            )?,
+            None => writeln!(
+                f,
+                "game {} ({}): no winner after {} turns",
+                self.game, self.ruleset, self.turns
+            )?,
+        }
+
+        for outcome in self.snakes.iter() {
+            match &outcome.death {
+                Some((turn, elimination)) => {
+                    write!(
+                        f,
+                        "  {:<12} length {:>3}, eliminated on turn {} by {}",
+                        outcome.id, outcome.length, turn, elimination.cause
+                    )?;
+
+                    match &elimination.by {
+                        Some(by) if by != &outcome.id => writeln!(f, " with {}", by)?,
+                        _ => writeln!(f)?,
+                    }
+                }
+                None => writeln!(
+                    f,
+                    "  {:<12} length {:>3}, survived",
+                    outcome.id, outcome.length
+                )?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use anyhow::Result;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::strategy::{Decision, Greedy, Pipeline, Weights};
+
+    use super::*;
+
+    /// Panics on every move, and notes which snakes it was asked to wrap up.
+    #[derive(Default)]
+    struct Panicky {
+        ended: Mutex<Vec<String>>,
+    }
+
+    impl Strategy for Panicky {
+        fn name(&self) -> &str {
+            "panicky"
+        }
+
+        fn decide(&self, _: &State, _: &mut Session, _: Deadline) -> Result<Decision> {
+            panic!("no idea");
+        }
+
+        fn end(&self, state: &State, _: &mut Session) {
+            self.ended
+                .lock()
+                .expect("not poisoned")
+                .push(state.you.id.clone());
+        }
+    }
+
+    fn arena(name: &str, snakes: usize) -> Arena {
+        let greedy: Arc<dyn Strategy> =
+            Arc::new(Greedy::new(1.5, Pipeline::default(), Weights::default()));
+
+        Arena {
+            ruleset: Ruleset {
+                name: name.to_string(),
+                ..Default::default()
+            },
+            width: 11,
+            height: 11,
+            timeout: 500,
+            max_turns: 300,
+            snakes: vec![greedy; snakes],
+        }
+    }
+
+    #[test]
+    fn setup() {
+        let state = arena("standard", 4).setup("asdf", &mut StdRng::seed_from_u64(1));
+
+        assert_eq!(state.board.snakes.len(), 4);
+        assert_eq!(state.board.food.len(), 5);
+        assert!(state.board.snakes.iter().all(|snake| {
+            snake.length() == START_LENGTH
+                && [1, 9].contains(&snake.head.x)
+                && [1, 9].contains(&snake.head.y)
+        }));
+    }
+
+    #[test]
+    fn play() {
+        let arena = arena("royale", 2);
+        let summary = arena.play("asdf", &mut StdRng::seed_from_u64(1));
+
+        assert_eq!(summary.snakes.len(), 2);
+        assert!(summary.turns > 0 && summary.turns <= arena.max_turns);
+
+        let dead = summary.snakes.iter().filter(|o| o.death.is_some()).count();
+        match &summary.winner {
+            Some(winner) => {
+                assert_eq!(dead, 1);
+                assert!(summary
+                    .snakes
+                    .iter()
+                    .any(|o| &o.id == winner && o.death.is_none()));
+            }
+            None => assert!(dead == 2 || summary.turns == arena.max_turns),
+        }
+    }
+
+    #[test]
+    fn panics_and_ends() {
+        let panicky = Arc::new(Panicky::default());
+        let arena = Arena {
+            snakes: vec![panicky.clone(), panicky.clone()],
+            ..arena("standard", 0)
+        };
+
+        // Every move panics, so both snakes play safe moves until one of them is out.
+        let summary = arena.play("asdf", &mut StdRng::seed_from_u64(1));
+        assert!(summary.turns > 0);
+
+        let mut ended = panicky.ended.lock().expect("not poisoned").clone();
+        ended.sort();
+        let mut ids: Vec<String> = summary.snakes.iter().map(|o| o.id.clone()).collect();
+        ids.sort();
+        assert!(summary.snakes.iter().any(|o| o.death.is_some()));
+        assert_eq!(ended, ids);
+    }
+}
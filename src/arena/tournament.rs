@@ -0,0 +1,293 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use rand::{seq::SliceRandom, Rng};
+use serde::Deserialize;
+
+use crate::config::load_config;
+use crate::game::{Ruleset, State};
+use crate::search::Deadline;
+use crate::session::Session;
+use crate::strategy::{Decision, Filter, Greedy, Info, Pipeline, Strategy, StrategyKind, Weights};
+
+use super::elo::Ratings;
+use super::{Arena, Summary};
+
+/// One configuration of a strategy entered into the tournament.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Variant {
+    pub name: String,
+    #[serde(default = "default_strategy")]
+    pub strategy: StrategyKind,
+    #[serde(default = "default_hunger_coefficient")]
+    pub hunger_coefficient: f32,
+    /// Greedy filters to run, defaulting to all of them.
+    #[serde(default)]
+    pub filters: Option<Vec<Filter>>,
+    #[serde(default)]
+    pub weights: Weights,
+}
+
+impl Variant {
+    pub fn build(&self) -> Arc<dyn Strategy> {
+        let pipeline = match &self.filters {
+            Some(filters) => Pipeline {
+                filters: filters.clone(),
+            },
+            None => Pipeline::default(),
+        };
+
+        let greedy = Greedy::new(self.hunger_coefficient, pipeline, self.weights.clone());
+
+        Arc::new(Named {
+            name: self.name.clone(),
+            strategy: self.strategy.build(greedy),
+        })
+    }
+}
+
+/// A kind of game the variants are played in.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Setup {
+    pub name: String,
+    #[serde(default = "default_ruleset")]
+    pub ruleset: String,
+    pub snakes: usize,
+    #[serde(default = "default_size")]
+    pub width: i16,
+    #[serde(default = "default_size")]
+    pub height: i16,
+}
+
+impl Setup {
+    /// Four-way standard and royale games, and one on one duels.
+    pub fn defaults() -> Vec<Setup> {
+        let setup = |name: &str, ruleset: &str, snakes: usize| Setup {
+            name: name.to_string(),
+            ruleset: ruleset.to_string(),
+            snakes,
+            width: default_size(),
+            height: default_size(),
+        };
+
+        vec![
+            setup("standard", "standard", 4),
+            setup("royale", "royale", 4),
+            setup("duel", "standard", 2),
+        ]
+    }
+}
+
+/// The variants to rate and the setups to play them in, loadable from a TOML or JSON file.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Tournament {
+    pub variants: Vec<Variant>,
+    #[serde(default = "Setup::defaults")]
+    pub setups: Vec<Setup>,
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+    #[serde(default = "default_max_turns")]
+    pub max_turns: u16,
+}
+
+impl Default for Tournament {
+    /// Greedy at a few hunger coefficients.
+    fn default() -> Self {
+        let variant = |hunger_coefficient: f32| Variant {
+            name: format!("hunger-{:.1}", hunger_coefficient),
+            strategy: default_strategy(),
+            hunger_coefficient,
+            filters: None,
+            weights: Weights::default(),
+        };
+
+        Tournament {
+            variants: vec![variant(1.0), variant(1.5), variant(2.0)],
+            setups: Setup::defaults(),
+            timeout: default_timeout(),
+            max_turns: default_max_turns(),
+        }
+    }
+}
+
+impl Tournament {
+    pub fn load(path: &Path) -> Result<Tournament> {
+        load_config(path, "tournament")
+    }
+
+    /// Plays `games` games of every setup, each between a random draw of the variants, and rates
+    /// the variants on the results as they come in. Setups with more seats than there are
+    /// variants enter some variants more than once.
+    pub fn run<R: Rng>(&self, games: usize, ratings: &mut Ratings, rng: &mut R) -> Vec<Summary> {
+        let strategies: Vec<Arc<dyn Strategy>> = self
+            .variants
+            .iter()
+            .map(|variant| variant.build())
+            .collect();
+
+        let mut summaries: Vec<Summary> = Vec::new();
+        if strategies.is_empty() {
+            return summaries;
+        }
+
+        for setup in self.setups.iter() {
+            for number in 0..games {
+                let mut seats = strategies.clone();
+                seats.shuffle(rng);
+                let snakes: Vec<Arc<dyn Strategy>> =
+                    seats.into_iter().cycle().take(setup.snakes).collect();
+
+                let arena = Arena {
+                    ruleset: Ruleset {
+                        name: setup.ruleset.clone(),
+                        ..Default::default()
+                    },
+                    width: setup.width,
+                    height: setup.height,
+                    timeout: self.timeout,
+                    max_turns: self.max_turns,
+                    snakes,
+                };
+
+                let summary = arena.play(&format!("{}-{}", setup.name, number), rng);
+                ratings.record(&summary);
+                summaries.push(summary);
+            }
+        }
+
+        summaries
+    }
+}
+
+/// A strategy entered under its variant's name, so games and ratings tell variants apart.
+struct Named {
+    name: String,
+    strategy: Arc<dyn Strategy>,
+}
+
+impl Strategy for Named {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn info(&self) -> Info {
+        self.strategy.info()
+    }
+
+    fn start(&self, state: &State, session: &mut Session) {
+        self.strategy.start(state, session)
+    }
+
+    fn decide(&self, state: &State, session: &mut Session, deadline: Deadline) -> Result<Decision> {
+        self.strategy.decide(state, session, deadline)
+    }
+
+    fn end(&self, state: &State, session: &mut Session) {
+        self.strategy.end(state, session)
+    }
+}
+
+fn default_strategy() -> StrategyKind {
+    StrategyKind::Greedy
+}
+
+fn default_hunger_coefficient() -> f32 {
+    1.5
+}
+
+fn default_ruleset() -> String {
+    "standard".to_string()
+}
+
+fn default_size() -> i16 {
+    11
+}
+
+fn default_timeout() -> u64 {
+    500
+}
+
+fn default_max_turns() -> u16 {
+    1000
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn deserialize() {
+        let tournament: Tournament = toml::from_str(
+            r#"
+            [[variants]]
+            name = "hungry"
+            hunger_coefficient = 2.0
+
+            [[variants]]
+            name = "scored"
+            strategy = "scored"
+            weights = { pocket = 20.0 }
+
+            [[setups]]
+            name = "duel"
+            snakes = 2
+            "#,
+        )
+        .expect("must parse");
+
+        assert_eq!(tournament.variants.len(), 2);
+        assert_eq!(tournament.variants[0].strategy, StrategyKind::Greedy);
+        assert_eq!(tournament.variants[1].weights.pocket, 20.0);
+        assert_eq!(tournament.variants[1].weights.food, 3.0);
+        assert_eq!(tournament.setups[0].ruleset, "standard");
+        assert_eq!(tournament.max_turns, 1000);
+        assert_eq!(tournament.variants[1].build().name(), "scored");
+    }
+
+    #[test]
+    fn run() {
+        let setup = |name: &str, snakes: usize| Setup {
+            name: name.to_string(),
+            ruleset: "standard".to_string(),
+            snakes,
+            width: 7,
+            height: 7,
+        };
+        let tournament = Tournament {
+            setups: vec![setup("duel", 2), setup("four", 4)],
+            max_turns: 100,
+            ..Tournament::default()
+        };
+
+        let mut ratings = Ratings::default();
+        let summaries = tournament.run(2, &mut ratings, &mut StdRng::seed_from_u64(1));
+
+        // Duels seat two of the three variants, four-way games all three, one twice, and each
+        // variant plays a game once however many seats it has.
+        assert_eq!(summaries.len(), 4);
+        assert_eq!(
+            ratings
+                .records
+                .values()
+                .map(|record| record.games)
+                .sum::<usize>(),
+            2 * 2 + 2 * 3
+        );
+        assert!(ratings.records.values().all(|record| record.games >= 2));
+        assert!(
+            ratings
+                .records
+                .values()
+                .map(|record| record.wins)
+                .sum::<usize>()
+                <= summaries.len()
+        );
+        assert!(ratings
+            .records
+            .keys()
+            .all(|name| name.starts_with("hunger-")));
+    }
+}
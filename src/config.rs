@@ -0,0 +1,45 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+
+/// Reads a config file, TOML if it ends in `.toml` and JSON otherwise. `what` names it in the
+/// error should it not read.
+pub fn load_config<T: DeserializeOwned>(path: &Path, what: &str) -> Result<T> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {} {}", what, path.display()))?;
+
+    let config = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&contents)?,
+        _ => serde_json::from_str(&contents)?,
+    };
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn toml_or_json() {
+        let dir = std::env::temp_dir().join(format!("battlesnake-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("must create");
+
+        std::fs::write(dir.join("a.toml"), "a = 1").expect("must write");
+        std::fs::write(dir.join("a.json"), r#"{"a": 2}"#).expect("must write");
+
+        let toml: HashMap<String, u8> = load_config(&dir.join("a.toml"), "test").expect("toml");
+        let json: HashMap<String, u8> = load_config(&dir.join("a.json"), "test").expect("json");
+        assert_eq!(toml["a"], 1);
+        assert_eq!(json["a"], 2);
+
+        let missing = load_config::<HashMap<String, u8>>(&dir.join("b.toml"), "test")
+            .expect_err("doesn't exist");
+        assert!(missing.to_string().starts_with("failed to read test"));
+
+        std::fs::remove_dir_all(&dir).expect("must clean up");
+    }
+}
@@ -1,21 +1,22 @@
 use std::collections::{HashMap, HashSet};
 
-use colored::*;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
+use super::grid::Grid;
 use super::mv::Move;
 use super::point::Point;
+use super::render::Renderer;
 use super::snake::Snake;
 
-const COLORS: [&str; 5] = ["blue", "red", "yellow", "purple", "cyan"];
-
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Board {
     pub height: i16,
     pub width: i16,
     pub food: Vec<Point>,
     pub hazards: Vec<Point>,
     pub snakes: Vec<Snake>,
+    #[serde(skip)]
+    pub wrapped: bool,
 }
 
 impl Board {
@@ -23,6 +24,20 @@ impl Board {
         0 <= point.x && point.x < self.width && 0 <= point.y && point.y < self.height
     }
 
+    /// Shifts a point by a move, wrapping around the edges on wrapped boards.
+    pub fn shift(&self, point: &Point, mv: &Move) -> Point {
+        let shifted = point.shift(mv);
+
+        if self.wrapped {
+            Point::new(
+                shifted.x.rem_euclid(self.width),
+                shifted.y.rem_euclid(self.height),
+            )
+        } else {
+            shifted
+        }
+    }
+
     pub fn food_at(&self, point: &Point) -> bool {
         self.food.contains(point)
     }
@@ -41,7 +56,9 @@ impl Board {
             .min_by(|a, b| point.distance(a).cmp(&point.distance(b)))
     }
 
+    #[allow(clippy::clone_on_copy)]
     pub fn pocket_sizes(&self) -> HashMap<Point, usize> {
+        let grid = self.grid();
         let mut checked: HashSet<Point> = HashSet::new();
         let mut pockets: Vec<HashSet<Point>> = Vec::new();
 
@@ -54,7 +71,7 @@ impl Board {
 
                 checked.insert(point);
 
-                if let Some(pocket) = self.pocket_at(&point) {
+                if let Some(pocket) = self.pocket_in(&grid, &point) {
                     checked.extend(pocket.iter());
                     pockets.push(pocket);
                 }
@@ -71,13 +88,17 @@ impl Board {
         sizes
     }
 
+    #[cfg(test)]
     fn pocket_at(&self, point: &Point) -> Option<HashSet<Point>> {
-        let mut queue = vec![point.clone()];
+        self.pocket_in(&self.grid(), point)
+    }
+
+    fn pocket_in(&self, grid: &Grid, point: &Point) -> Option<HashSet<Point>> {
+        let mut queue = vec![*point];
 
         let mut pocket = HashSet::new();
         while let Some(point) = queue.pop() {
-            if pocket.contains(&point) || !self.in_bounds(&point) || self.snake_at(&point).is_some()
-            {
+            if pocket.contains(&point) || !self.in_bounds(&point) || grid.occupied(&point) {
                 continue;
             }
 
@@ -85,7 +106,7 @@ impl Board {
 
             Move::all()
                 .iter()
-                .map(|mv| point.shift(mv))
+                .map(|mv| self.shift(&point, mv))
                 .for_each(|neighbor| queue.push(neighbor));
         }
 
@@ -97,52 +118,17 @@ impl Board {
     }
 }
 
+#[allow(clippy::to_string_trait_impl)]
 impl ToString for Board {
     fn to_string(&self) -> String {
-        let mut output = String::new();
-
-        for y in (0..self.height).rev() {
-            output.push_str(&format!("{}  ", y));
-
-            for x in 0..self.width {
-                let point = Point::new(x, y);
-
-                let string = if self.food_at(&point) {
-                    "$".green().to_string()
-                } else if let Some(snake) = self.snake_at(&point) {
-                    let index = self
-                        .snakes
-                        .iter()
-                        .position(|s| s == snake)
-                        .expect("snake not found");
-
-                    let color = COLORS[index % COLORS.len()];
-
-                    if point == snake.head { "@" } else { "#" }
-                        .color(color)
-                        .to_string()
-                } else {
-                    ".".to_string()
-                };
-
-                output.push_str(&(string + " "));
-            }
-
-            output += "\n"
-        }
-
-        let bottom = (0..self.width)
-            .map(|i| i.to_string())
-            .collect::<Vec<String>>()
-            .join(" ");
-        output.push_str(&format!("\n   {}\n", bottom));
-
-        output
+        Renderer::default().render(self)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::game::State;
+
     use super::*;
 
     #[test]
@@ -153,6 +139,7 @@ mod tests {
             food: vec![],
             hazards: vec![],
             snakes: vec![],
+            wrapped: false,
         };
 
         assert_eq!(board.in_bounds(&Point { x: 0, y: 0 }), true);
@@ -162,6 +149,34 @@ mod tests {
         assert_eq!(board.in_bounds(&Point { x: 10, y: 10 }), false);
     }
 
+    #[test]
+    fn shift() {
+        let mut board = Board {
+            height: 10,
+            width: 10,
+            food: vec![],
+            hazards: vec![],
+            snakes: vec![],
+            wrapped: false,
+        };
+
+        assert_eq!(
+            board.shift(&Point::new(0, 9), &Move::Left),
+            Point::new(-1, 9)
+        );
+
+        board.wrapped = true;
+        assert_eq!(
+            board.shift(&Point::new(0, 9), &Move::Left),
+            Point::new(9, 9)
+        );
+        assert_eq!(board.shift(&Point::new(0, 9), &Move::Up), Point::new(0, 0));
+        assert_eq!(
+            board.shift(&Point::new(3, 3), &Move::Right),
+            Point::new(4, 3)
+        );
+    }
+
     #[test]
     fn closest_food() {
         let board = Board {
@@ -170,6 +185,7 @@ mod tests {
             food: vec![Point::new(1, 2), Point::new(1, 5)],
             hazards: vec![],
             snakes: vec![],
+            wrapped: false,
         };
 
         assert_eq!(
@@ -190,34 +206,16 @@ mod tests {
 
     #[test]
     fn pocket_at() {
-        let board = Board {
-            height: 5,
-            width: 5,
-            food: vec![],
-            hazards: vec![],
-            snakes: vec![
-                Snake {
-                    id: "a".to_string(),
-                    health: 10,
-                    body: vec![
-                        Point::new(3, 4),
-                        Point::new(3, 3),
-                        Point::new(3, 2),
-                        Point::new(2, 2),
-                        Point::new(1, 2),
-                        Point::new(1, 1),
-                        Point::new(1, 0),
-                    ],
-                    head: Point::new(3, 4),
-                },
-                Snake {
-                    id: "b".to_string(),
-                    health: 10,
-                    body: vec![Point::new(4, 2), Point::new(4, 1)],
-                    head: Point::new(4, 2),
-                },
-            ],
-        };
+        let board = "
+            4  . . . A .
+            3  . . . a .
+            2  . a a a B
+            1  . a . . b
+            0  . a . . .
+        "
+        .parse::<State>()
+        .expect("must parse")
+        .board;
 
         assert_eq!(
             board.pocket_at(&Point::new(0, 0)).expect("must be some"),
@@ -255,34 +253,16 @@ mod tests {
 
     #[test]
     fn pocket_sizes() {
-        let board = Board {
-            height: 5,
-            width: 5,
-            food: vec![],
-            hazards: vec![],
-            snakes: vec![
-                Snake {
-                    id: "a".to_string(),
-                    health: 10,
-                    body: vec![
-                        Point::new(3, 4),
-                        Point::new(3, 3),
-                        Point::new(3, 2),
-                        Point::new(2, 2),
-                        Point::new(1, 2),
-                        Point::new(1, 1),
-                        Point::new(1, 0),
-                    ],
-                    head: Point::new(3, 4),
-                },
-                Snake {
-                    id: "b".to_string(),
-                    health: 10,
-                    body: vec![Point::new(4, 2), Point::new(4, 1)],
-                    head: Point::new(4, 2),
-                },
-            ],
-        };
+        let board = "
+            4  . . . A .
+            3  . . . a .
+            2  . a a a B
+            1  . a . . b
+            0  . a . . .
+        "
+        .parse::<State>()
+        .expect("must parse")
+        .board;
 
         let pocket_sizes = board.pocket_sizes();
 
@@ -0,0 +1,128 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::board::Board;
+use super::mv::Move;
+use super::point::Point;
+
+/// The space reachable from a point once tails retreating out of the way are accounted for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimedPocket {
+    pub area: usize,
+    /// The first turn a cell that's currently part of a body can be stepped on, if ever.
+    pub escape: Option<usize>,
+}
+
+impl Board {
+    /// How many turns until each body segment moves away, assuming no one eats. A segment `n`
+    /// places from the end of its snake is gone after `n` turns, so the tail frees up next turn.
+    pub fn vacate_turns(&self) -> HashMap<Point, usize> {
+        let mut turns: HashMap<Point, usize> = HashMap::new();
+
+        for snake in self.snakes.iter() {
+            for (index, point) in snake.body.iter().enumerate() {
+                let turn = snake.length() - index;
+                let entry = turns.entry(*point).or_insert(turn);
+                *entry = (*entry).max(turn);
+            }
+        }
+
+        turns
+    }
+
+    /// Flood fills outwards from `point`, which is reached on `turn`. A body segment is treated as
+    /// a wall until the turn we'd reach it is at least the number of turns it takes to move away.
+    /// Returns `None` if `point` itself can't be entered on `turn`.
+    pub fn timed_pocket_at(&self, point: &Point, turn: usize) -> Option<TimedPocket> {
+        let vacate_turns = self.vacate_turns();
+        let passable = |point: &Point, turn: usize| {
+            self.in_bounds(point)
+                && match vacate_turns.get(point) {
+                    Some(vacate) => turn >= *vacate,
+                    None => true,
+                }
+        };
+
+        if !passable(point, turn) {
+            return None;
+        }
+
+        let mut visited: HashSet<Point> = HashSet::from([*point]);
+        let mut queue: VecDeque<(Point, usize)> = VecDeque::from([(*point, turn)]);
+        let mut escape: Option<usize> = None;
+
+        while let Some((point, turn)) = queue.pop_front() {
+            if vacate_turns.contains_key(&point) {
+                escape = Some(escape.map_or(turn, |escape| escape.min(turn)));
+            }
+
+            for mv in Move::all() {
+                let neighbor = self.shift(&point, &mv);
+                if visited.contains(&neighbor) || !passable(&neighbor, turn + 1) {
+                    continue;
+                }
+
+                visited.insert(neighbor);
+                queue.push_back((neighbor, turn + 1));
+            }
+        }
+
+        Some(TimedPocket {
+            area: visited.len(),
+            escape,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::snake::Snake;
+
+    use super::*;
+
+    #[test]
+    fn timed_pocket_at() {
+        // A 3x3 board with the right two columns walled off by a snake whose tail sits at the
+        // bottom of the wall.
+        let board = Board {
+            height: 3,
+            width: 3,
+            food: vec![],
+            hazards: vec![],
+            snakes: vec![Snake {
+                id: "a".to_string(),
+                health: 100,
+                head: Point::new(2, 2),
+                body: vec![
+                    Point::new(2, 2),
+                    Point::new(1, 2),
+                    Point::new(1, 1),
+                    Point::new(1, 0),
+                ],
+                ..Default::default()
+            }],
+            wrapped: false,
+        };
+
+        // The old flood fill only sees the left column.
+        assert_eq!(board.pocket_sizes().get(&Point::new(0, 0)), Some(&3));
+
+        // Starting at the bottom left, the tail at (1, 0) is gone by the time we get next to it.
+        assert_eq!(
+            board.timed_pocket_at(&Point::new(0, 0), 1),
+            Some(TimedPocket {
+                area: 9,
+                escape: Some(2),
+            })
+        );
+
+        // Starting at the top left, the wall has mostly retreated by the time we get around.
+        let pocket = board
+            .timed_pocket_at(&Point::new(0, 2), 1)
+            .expect("must be some");
+        assert_eq!(pocket.area, 9);
+        assert_eq!(pocket.escape, Some(3));
+
+        // The neck can't be entered next turn.
+        assert_eq!(board.timed_pocket_at(&Point::new(1, 2), 1), None);
+    }
+}
@@ -1,12 +1,163 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Game {
     pub id: String,
     pub ruleset: Ruleset,
+    #[serde(default)]
+    pub map: String,
+    /// Where the game was started from, such as `league` or `custom`.
+    #[serde(default)]
+    pub source: String,
+    /// How long the engine waits for a move, in milliseconds.
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
 }
 
-#[derive(Deserialize, Debug)]
+impl Default for Game {
+    fn default() -> Self {
+        Game {
+            id: String::new(),
+            ruleset: Ruleset::default(),
+            map: String::new(),
+            source: String::new(),
+            timeout: default_timeout(),
+        }
+    }
+}
+
+fn default_timeout() -> u64 {
+    500
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Ruleset {
     pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub settings: Settings,
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Ruleset {
+            name: "standard".to_string(),
+            version: String::new(),
+            settings: Settings::default(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Mode {
+    Standard,
+    Solo,
+    Royale,
+    Constrictor,
+    Wrapped,
+    Squad,
+}
+
+impl Ruleset {
+    pub fn mode(&self) -> Mode {
+        match self.name.as_str() {
+            "solo" => Mode::Solo,
+            "royale" => Mode::Royale,
+            "constrictor" => Mode::Constrictor,
+            "wrapped" => Mode::Wrapped,
+            "squad" => Mode::Squad,
+            _ => Mode::Standard,
+        }
+    }
+
+    /// Whether squadmates can move through each other's bodies.
+    pub fn squad_body_collisions(&self) -> bool {
+        self.mode() == Mode::Squad && self.settings.squad.allow_body_collisions
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub food_spawn_chance: u16,
+    pub minimum_food: u16,
+    pub hazard_damage_per_turn: u16,
+    pub hazard_map: String,
+    pub hazard_map_author: String,
+    pub royale: RoyaleSettings,
+    pub squad: SquadSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            food_spawn_chance: 15,
+            minimum_food: 1,
+            hazard_damage_per_turn: 14,
+            hazard_map: String::new(),
+            hazard_map_author: String::new(),
+            royale: RoyaleSettings::default(),
+            squad: SquadSettings::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RoyaleSettings {
+    pub shrink_every_n_turns: u16,
+}
+
+impl Default for RoyaleSettings {
+    fn default() -> Self {
+        RoyaleSettings {
+            shrink_every_n_turns: 25,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SquadSettings {
+    pub allow_body_collisions: bool,
+    pub shared_elimination: bool,
+    pub shared_health: bool,
+    pub shared_length: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings() {
+        let ruleset: Ruleset = serde_json::from_str(
+            r#"{
+                "name": "royale",
+                "settings": {
+                    "foodSpawnChance": 25,
+                    "minimumFood": 2,
+                    "hazardDamagePerTurn": 15,
+                    "royale": { "shrinkEveryNTurns": 10 },
+                    "squad": { "allowBodyCollisions": true }
+                }
+            }"#,
+        )
+        .expect("failed to parse ruleset");
+
+        assert_eq!(ruleset.mode(), Mode::Royale);
+        assert_eq!(ruleset.settings.food_spawn_chance, 25);
+        assert_eq!(ruleset.settings.minimum_food, 2);
+        assert_eq!(ruleset.settings.hazard_damage_per_turn, 15);
+        assert_eq!(ruleset.settings.royale.shrink_every_n_turns, 10);
+        assert_eq!(ruleset.settings.squad.allow_body_collisions, true);
+        assert_eq!(ruleset.settings.squad.shared_health, false);
+
+        let ruleset: Ruleset =
+            serde_json::from_str(r#"{ "name": "wrapped" }"#).expect("failed to parse ruleset");
+
+        assert_eq!(ruleset.mode(), Mode::Wrapped);
+        assert_eq!(ruleset.settings.hazard_damage_per_turn, 14);
+    }
 }
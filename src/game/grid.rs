@@ -0,0 +1,234 @@
+use super::board::Board;
+use super::point::Point;
+
+const EMPTY: u16 = u16::MAX;
+
+/// A fixed-size set of cells, one bit each.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Bits {
+    words: Vec<u64>,
+}
+
+impl Bits {
+    pub fn new(len: usize) -> Bits {
+        Bits {
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    pub fn set(&mut self, index: usize) {
+        self.words[index / 64] |= 1 << (index % 64);
+    }
+
+    pub fn clear(&mut self, index: usize) {
+        self.words[index / 64] &= !(1 << (index % 64));
+    }
+
+    pub fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+}
+
+/// A flat, cheap to clone view of a `Board` with constant time lookups, for code that queries the
+/// same board many times over. Built from the board whenever it's needed, so it's never stale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid {
+    width: i16,
+    height: i16,
+    food: Bits,
+    hazards: Bits,
+    /// The index into `Board::snakes` of the snake covering each cell.
+    snakes: Vec<u16>,
+}
+
+impl Grid {
+    pub fn new(width: i16, height: i16) -> Grid {
+        let len = (width.max(0) as usize) * (height.max(0) as usize);
+
+        Grid {
+            width,
+            height,
+            food: Bits::new(len),
+            hazards: Bits::new(len),
+            snakes: vec![EMPTY; len],
+        }
+    }
+
+    pub fn index(&self, point: &Point) -> Option<usize> {
+        if 0 <= point.x && point.x < self.width && 0 <= point.y && point.y < self.height {
+            Some(point.y as usize * self.width as usize + point.x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn food_at(&self, point: &Point) -> bool {
+        self.index(point).is_some_and(|index| self.food.get(index))
+    }
+
+    pub fn hazard_at(&self, point: &Point) -> bool {
+        self.index(point)
+            .is_some_and(|index| self.hazards.get(index))
+    }
+
+    /// The index into `Board::snakes` of the snake covering `point`, if any.
+    pub fn snake_at(&self, point: &Point) -> Option<usize> {
+        match self.index(point).map(|index| self.snakes[index]) {
+            Some(EMPTY) | None => None,
+            Some(snake) => Some(snake as usize),
+        }
+    }
+
+    pub fn occupied(&self, point: &Point) -> bool {
+        self.snake_at(point).is_some()
+    }
+
+    pub fn set_food(&mut self, point: &Point, food: bool) {
+        if let Some(index) = self.index(point) {
+            if food {
+                self.food.set(index);
+            } else {
+                self.food.clear(index);
+            }
+        }
+    }
+
+    pub fn set_hazard(&mut self, point: &Point, hazard: bool) {
+        if let Some(index) = self.index(point) {
+            if hazard {
+                self.hazards.set(index);
+            } else {
+                self.hazards.clear(index);
+            }
+        }
+    }
+
+    /// Marks `point` as covered by the snake at `snake` in `Board::snakes`.
+    ///
+    /// # Panics
+    ///
+    /// If `snake` is `u16::MAX` or more, which is far more snakes than fit on any board.
+    pub fn occupy(&mut self, point: &Point, snake: usize) {
+        let snake = u16::try_from(snake)
+            .ok()
+            .filter(|snake| *snake != EMPTY)
+            .expect("too many snakes to index");
+
+        if let Some(index) = self.index(point) {
+            self.snakes[index] = snake;
+        }
+    }
+
+    pub fn vacate(&mut self, point: &Point) {
+        if let Some(index) = self.index(point) {
+            self.snakes[index] = EMPTY;
+        }
+    }
+
+    pub fn food_count(&self) -> usize {
+        self.food.count()
+    }
+}
+
+impl From<&Board> for Grid {
+    fn from(board: &Board) -> Self {
+        let mut grid = Grid::new(board.width, board.height);
+
+        board.food.iter().for_each(|food| grid.set_food(food, true));
+        board
+            .hazards
+            .iter()
+            .for_each(|hazard| grid.set_hazard(hazard, true));
+
+        // Earlier snakes win any cell shared with a later one, as they did when searching the list.
+        for (index, snake) in board.snakes.iter().enumerate().rev() {
+            snake
+                .body
+                .iter()
+                .for_each(|point| grid.occupy(point, index));
+        }
+
+        grid
+    }
+}
+
+impl Board {
+    pub fn grid(&self) -> Grid {
+        Grid::from(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::snake::Snake;
+
+    use super::*;
+
+    #[test]
+    fn bits() {
+        let mut bits = Bits::new(130);
+
+        bits.set(0);
+        bits.set(64);
+        bits.set(129);
+        assert!(bits.get(64));
+        assert!(!bits.get(65));
+        assert_eq!(bits.count(), 3);
+
+        bits.clear(64);
+        assert!(!bits.get(64));
+        assert_eq!(bits.count(), 2);
+    }
+
+    #[test]
+    fn from_board() {
+        let board = Board {
+            height: 11,
+            width: 11,
+            food: vec![Point::new(10, 10)],
+            hazards: vec![Point::new(0, 10)],
+            snakes: vec![
+                Snake {
+                    id: "a".to_string(),
+                    health: 100,
+                    head: Point::new(1, 1),
+                    body: vec![Point::new(1, 1), Point::new(1, 0)],
+                    ..Default::default()
+                },
+                Snake {
+                    id: "b".to_string(),
+                    health: 100,
+                    head: Point::new(5, 5),
+                    body: vec![Point::new(5, 5), Point::new(5, 6)],
+                    ..Default::default()
+                },
+            ],
+            wrapped: false,
+        };
+
+        let mut grid = board.grid();
+
+        assert!(grid.food_at(&Point::new(10, 10)));
+        assert!(!grid.food_at(&Point::new(10, 9)));
+        assert!(grid.hazard_at(&Point::new(0, 10)));
+        assert_eq!(grid.snake_at(&Point::new(1, 0)), Some(0));
+        assert_eq!(grid.snake_at(&Point::new(5, 6)), Some(1));
+        assert_eq!(grid.snake_at(&Point::new(4, 4)), None);
+        assert_eq!(grid.snake_at(&Point::new(-1, 4)), None);
+
+        grid.vacate(&Point::new(1, 0));
+        grid.occupy(&Point::new(1, 2), 0);
+        grid.set_food(&Point::new(10, 10), false);
+
+        assert!(!grid.occupied(&Point::new(1, 0)));
+        assert!(grid.occupied(&Point::new(1, 2)));
+        assert_eq!(grid.food_count(), 0);
+    }
+}
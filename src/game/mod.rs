@@ -1,9 +1,26 @@
 mod board;
+mod flood;
+#[allow(clippy::module_inception)]
 mod game;
+mod grid;
 mod mv;
+mod parse;
 mod point;
+mod render;
+mod rules;
 mod snake;
+mod spawn;
 mod state;
+mod territory;
 
+pub use board::Board;
+pub use flood::TimedPocket;
+pub use game::{Game, Mode, RoyaleSettings, Ruleset, Settings, SquadSettings};
+pub use grid::{Bits, Grid};
 pub use mv::Move;
+pub use point::Point;
+pub use render::Renderer;
+pub use rules::{Elimination, EliminationCause};
+pub use snake::{Customizations, Snake, MAX_HEALTH};
 pub use state::State;
+pub use territory::Territory;
@@ -1,6 +1,6 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, PartialEq, Eq, Copy, Clone, Debug, Hash)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum Move {
     Up,
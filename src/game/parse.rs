@@ -0,0 +1,343 @@
+use std::collections::{BTreeMap, HashSet};
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use super::board::Board;
+use super::game::{Game, Mode, Ruleset};
+use super::mv::Move;
+use super::point::Point;
+use super::snake::{Snake, MAX_HEALTH};
+use super::state::State;
+
+/// Parses a picture of the board, laid out as `Renderer` draws it with the top row first:
+///
+/// ```text
+/// turn: 12
+/// ruleset: royale
+///
+/// 4  . . . A .
+/// 3  . . . a .
+/// 2  . a a a B
+/// 1  . a . ~ b
+/// 0  $ a . ~ .
+///
+///    0 1 2 3 4
+///
+/// A: id=you health=50
+/// B: length=4
+/// ```
+///
+/// Each snake is a letter, upper case for its head and lower case for the rest of its body, which
+/// is traced outwards from the head. `$` is food, `~` a hazard and `.` an empty cell. `@` and `#`
+/// stand for `A` and `a`. Row and column labels and the spaces
+/// between cells are optional.
+///
+/// Snakes can be annotated with their `id`, defaulting to their letter in lower case, their
+/// `health`, defaulting to full, and their `length`, which stacks extra segments on the tail.
+/// `you` is snake `A` unless another is annotated with `you`.
+impl FromStr for State {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<State> {
+        let mut rows: Vec<Vec<char>> = Vec::new();
+        let mut annotations: BTreeMap<char, Vec<(String, String)>> = BTreeMap::new();
+        let mut turn: u16 = 0;
+        let mut ruleset = "standard".to_string();
+
+        for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if let Some((label, rest)) = line.split_once(':') {
+                match label.trim() {
+                    "turn" => turn = rest.trim().parse().context("bad turn")?,
+                    "ruleset" => ruleset = rest.trim().to_string(),
+                    label => {
+                        let letter = snake_letter(label)
+                            .ok_or_else(|| anyhow!("unknown annotation '{}'", label))?;
+                        annotations
+                            .entry(letter)
+                            .or_default()
+                            .extend(rest.split_whitespace().map(|pair| {
+                                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                                (key.to_string(), value.to_string())
+                            }));
+                    }
+                }
+
+                continue;
+            }
+
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let numeric = |token: &&str| token.chars().all(|c| c.is_ascii_digit());
+
+            // The column labels.
+            if tokens.iter().all(numeric) {
+                continue;
+            }
+
+            let cells = match tokens.first() {
+                Some(label) if numeric(label) => &tokens[1..],
+                _ => &tokens[..],
+            };
+            rows.push(cells.concat().chars().collect());
+        }
+
+        let height = rows.len();
+        let width = rows.first().map(|row| row.len()).unwrap_or(0);
+        if width == 0 {
+            bail!("no board");
+        }
+        if let Some(row) = rows.iter().find(|row| row.len() != width) {
+            bail!("rows must all be {} cells wide, found {}", width, row.len());
+        }
+
+        let mut board = Board {
+            height: height as i16,
+            width: width as i16,
+            food: vec![],
+            hazards: vec![],
+            snakes: vec![],
+            wrapped: false,
+        };
+        let mut heads: BTreeMap<char, Point> = BTreeMap::new();
+        let mut bodies: BTreeMap<char, HashSet<Point>> = BTreeMap::new();
+
+        for (row, cells) in rows.iter().enumerate() {
+            for (x, cell) in cells.iter().enumerate() {
+                let point = Point::new(x as i16, (height - 1 - row) as i16);
+
+                match cell {
+                    '.' => {}
+                    '$' => board.food.push(point),
+                    '~' => board.hazards.push(point),
+                    '@' | 'A'..='Z' => {
+                        let letter = if *cell == '@' { 'A' } else { *cell };
+                        if heads.insert(letter, point).is_some() {
+                            bail!("snake {} has more than one head", letter);
+                        }
+                    }
+                    '#' | 'a'..='z' => {
+                        let letter = if *cell == '#' {
+                            'A'
+                        } else {
+                            cell.to_ascii_uppercase()
+                        };
+                        bodies.entry(letter).or_default().insert(point);
+                    }
+                    _ => bail!("unknown cell '{}' at {:?}", cell, point),
+                }
+            }
+        }
+
+        if let Some(letter) = bodies.keys().find(|letter| !heads.contains_key(letter)) {
+            bail!("snake {} has no head", letter);
+        }
+
+        let mut you: Option<usize> = None;
+        for (letter, head) in heads {
+            let cells = bodies.remove(&letter).unwrap_or_default();
+            let mut body = trace(head, &cells)
+                .ok_or_else(|| anyhow!("snake {}'s body isn't one unbroken line", letter))?;
+
+            let mut snake = Snake {
+                id: letter.to_ascii_lowercase().to_string(),
+                health: MAX_HEALTH,
+                head,
+                body: vec![],
+                ..Default::default()
+            };
+
+            let mut is_you = false;
+            for (key, value) in annotations.remove(&letter).unwrap_or_default() {
+                match key.as_str() {
+                    "id" => snake.id = value,
+                    "health" => snake.health = value.parse().context("bad health")?,
+                    "length" => {
+                        let length: usize = value.parse().context("bad length")?;
+                        if length < body.len() {
+                            bail!("snake {} is longer than {}", letter, length);
+                        }
+
+                        let tail = *body.last().expect("body has a head");
+                        body.resize(length, tail);
+                    }
+                    "you" => is_you = true,
+                    _ => bail!("unknown annotation '{}' on snake {}", key, letter),
+                }
+            }
+
+            if is_you {
+                you = Some(board.snakes.len());
+            }
+
+            snake.body = body;
+            board.snakes.push(snake);
+        }
+
+        if let Some(letter) = annotations.keys().next() {
+            bail!("annotation for missing snake {}", letter);
+        }
+
+        let you = board
+            .snakes
+            .get(you.unwrap_or(0))
+            .cloned()
+            .ok_or_else(|| anyhow!("no snakes"))?;
+
+        let game = Game {
+            id: "test".to_string(),
+            ruleset: Ruleset {
+                name: ruleset,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        board.wrapped = game.ruleset.mode() == Mode::Wrapped;
+
+        Ok(State {
+            game,
+            turn,
+            board,
+            you,
+        })
+    }
+}
+
+fn snake_letter(label: &str) -> Option<char> {
+    let mut chars = label.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) if letter.is_ascii_uppercase() => Some(letter),
+        _ => None,
+    }
+}
+
+/// Orders a body by walking from the head through every one of its cells, one step at a time.
+fn trace(head: Point, cells: &HashSet<Point>) -> Option<Vec<Point>> {
+    fn walk(path: &mut Vec<Point>, cells: &HashSet<Point>) -> bool {
+        if path.len() == cells.len() + 1 {
+            return true;
+        }
+
+        let last = *path.last().expect("path starts at the head");
+        for mv in Move::all() {
+            let next = last.shift(&mv);
+            if !cells.contains(&next) || path.contains(&next) {
+                continue;
+            }
+
+            path.push(next);
+            if walk(path, cells) {
+                return true;
+            }
+            path.pop();
+        }
+
+        false
+    }
+
+    let mut path = vec![head];
+    if walk(&mut path, cells) {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let state: State = r#"
+            turn: 12
+            ruleset: royale
+
+            4  . . . A .
+            3  . . . a .
+            2  . a a a B
+            1  . a . ~ b
+            0  $ a . ~ .
+
+               0 1 2 3 4
+
+            A: id=you health=50
+            B: length=4
+        "#
+        .parse()
+        .expect("must parse");
+
+        assert_eq!(state.turn, 12);
+        assert_eq!(state.game.ruleset.name, "royale");
+        assert_eq!((state.board.width, state.board.height), (5, 5));
+        assert_eq!(state.board.food, vec![Point::new(0, 0)]);
+        assert_eq!(
+            state.board.hazards,
+            vec![Point::new(3, 1), Point::new(3, 0)]
+        );
+
+        assert_eq!(state.you.id, "you");
+        assert_eq!(state.you.health, 50);
+        assert_eq!(
+            state.you.body,
+            vec![
+                Point::new(3, 4),
+                Point::new(3, 3),
+                Point::new(3, 2),
+                Point::new(2, 2),
+                Point::new(1, 2),
+                Point::new(1, 1),
+                Point::new(1, 0),
+            ]
+        );
+
+        let b = &state.board.snakes[1];
+        assert_eq!(b.id, "b");
+        assert_eq!(b.health, MAX_HEALTH);
+        assert_eq!(
+            b.body,
+            vec![
+                Point::new(4, 2),
+                Point::new(4, 1),
+                Point::new(4, 1),
+                Point::new(4, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn coiled_and_unlabelled() {
+        let state: State = "
+            .bb.
+            .bB.
+            @#..
+            B: you
+        "
+        .parse()
+        .expect("must parse");
+
+        assert_eq!(state.you.id, "b");
+        assert_eq!(
+            state.you.body,
+            vec![
+                Point::new(2, 1),
+                Point::new(2, 2),
+                Point::new(1, 2),
+                Point::new(1, 1),
+            ]
+        );
+        assert_eq!(
+            state.board.snakes[0].body,
+            vec![Point::new(0, 0), Point::new(1, 0)]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!("".parse::<State>().is_err());
+        assert!(". a .".parse::<State>().is_err());
+        assert!("A . a".parse::<State>().is_err());
+        assert!("A a\n. .\nC: health=1".parse::<State>().is_err());
+        assert!("A a\n. . .".parse::<State>().is_err());
+        assert!("A a ?".parse::<State>().is_err());
+    }
+}
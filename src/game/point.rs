@@ -1,7 +1,7 @@
 use super::mv::Move;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug, PartialEq, Eq, Hash, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Copy, Clone, Default)]
 pub struct Point {
     pub x: i16,
     pub y: i16,
@@ -37,6 +37,7 @@ impl Point {
         (self.x - other.x).abs() + (self.y - other.y).abs()
     }
 
+    #[allow(clippy::unnecessary_sort_by)]
     pub fn towards(&self, other: &Point) -> Vec<Move> {
         let mut pairs: Vec<(Move, i16)> = Move::all()
             .into_iter()
@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+
+use colored::*;
+
+use super::board::Board;
+use super::mv::Move;
+use super::point::Point;
+use super::snake::Snake;
+use super::state::State;
+
+type Rgb = (u8, u8, u8);
+
+/// Colors for snakes without a color of their own, far enough apart to tell a full board apart.
+const PALETTE: [Rgb; 12] = [
+    (59, 130, 246),
+    (239, 68, 68),
+    (234, 179, 8),
+    (168, 85, 247),
+    (20, 184, 166),
+    (249, 115, 22),
+    (236, 72, 153),
+    (132, 204, 22),
+    (14, 165, 233),
+    (244, 63, 94),
+    (250, 204, 21),
+    (148, 163, 184),
+];
+
+const FOOD: Rgb = (34, 197, 94);
+const HAZARD: Rgb = (76, 29, 149);
+const CANDIDATE: Rgb = (255, 255, 255);
+
+/// Draws boards for the terminal, in the same layout `State`'s parser reads: each snake is a
+/// letter, upper case for its head, `$` is food, `~` a hazard and `.` an empty cell. Candidate
+/// moves are marked `+` where they land, and the chosen one `*`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Renderer {
+    /// Off for logs and snapshot tests.
+    pub color: bool,
+    pub hazards: bool,
+    /// Lists each snake's id, length and health under the board, as annotations the parser reads.
+    pub legend: bool,
+    /// Moves open to `you`, only drawn when rendering a whole state.
+    pub candidates: Vec<Move>,
+    pub chosen: Option<Move>,
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Renderer {
+            color: true,
+            hazards: true,
+            legend: false,
+            candidates: vec![],
+            chosen: None,
+        }
+    }
+}
+
+impl Renderer {
+    /// Everything, without color.
+    pub fn plain() -> Renderer {
+        Renderer {
+            color: false,
+            legend: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn render(&self, board: &Board) -> String {
+        self.draw(board, None, &HashMap::new())
+    }
+
+    /// The board with the turn and ruleset above it, `you` picked out in the legend, and the
+    /// candidate moves marked around its head.
+    pub fn render_state(&self, state: &State) -> String {
+        let mut marks: HashMap<Point, char> = HashMap::new();
+        for mv in self.candidates.iter() {
+            marks.insert(state.board.shift(&state.you.head, mv), '+');
+        }
+        if let Some(chosen) = &self.chosen {
+            marks.insert(state.board.shift(&state.you.head, chosen), '*');
+        }
+
+        format!(
+            "turn: {}\nruleset: {}\n\n{}",
+            state.turn,
+            state.game.ruleset.name,
+            self.draw(&state.board, Some(&state.you.id), &marks)
+        )
+    }
+
+    fn draw(&self, board: &Board, you: Option<&str>, marks: &HashMap<Point, char>) -> String {
+        let colors = colors(&board.snakes);
+        let mut cells: HashMap<Point, (char, Option<Rgb>)> = HashMap::new();
+
+        for (index, snake) in board.snakes.iter().enumerate() {
+            let letter = letter(index);
+
+            // Tail first, so the head wins wherever segments overlap.
+            for point in snake.body.iter().rev() {
+                let glyph = if *point == snake.head {
+                    letter
+                } else {
+                    letter.to_ascii_lowercase()
+                };
+
+                cells.insert(*point, (glyph, Some(colors[index])));
+            }
+        }
+
+        // Columns are as wide as the widest label, so they line up past ten.
+        let cell_width = (board.width - 1).max(0).to_string().len();
+        let label_width = (board.height - 1).max(0).to_string().len();
+        let mut output = String::new();
+
+        for y in (0..board.height).rev() {
+            output.push_str(&format!("{:>width$}  ", y, width = label_width));
+
+            for x in 0..board.width {
+                let point = Point::new(x, y);
+                let hazard = self.hazards && board.hazard_at(&point);
+
+                let (glyph, color) = match (cells.get(&point), marks.get(&point)) {
+                    (Some(cell), _) => *cell,
+                    (None, Some(mark)) => (*mark, Some(CANDIDATE)),
+                    (None, None) if board.food_at(&point) => ('$', Some(FOOD)),
+                    (None, None) if hazard => ('~', Some(HAZARD)),
+                    (None, None) => ('.', None),
+                };
+
+                let padded = format!("{:>width$}", glyph, width = cell_width);
+                let drawn = match (self.color, color) {
+                    (true, Some((r, g, b))) => {
+                        let drawn = padded.truecolor(r, g, b);
+                        let drawn = if marks.contains_key(&point) || cells.contains_key(&point) {
+                            drawn.bold()
+                        } else {
+                            drawn
+                        };
+
+                        if hazard {
+                            drawn.on_truecolor(HAZARD.0, HAZARD.1, HAZARD.2).to_string()
+                        } else {
+                            drawn.to_string()
+                        }
+                    }
+                    _ => padded,
+                };
+
+                output.push_str(&drawn);
+                output.push(' ');
+            }
+
+            output.truncate(output.trim_end_matches(' ').len());
+            output.push('\n');
+        }
+
+        let columns = (0..board.width)
+            .map(|x| format!("{:>width$}", x, width = cell_width))
+            .collect::<Vec<String>>()
+            .join(" ");
+        output.push_str(&format!(
+            "\n{:>width$}  {}\n",
+            "",
+            columns,
+            width = label_width
+        ));
+
+        if self.legend && !board.snakes.is_empty() {
+            output.push('\n');
+
+            for (index, snake) in board.snakes.iter().enumerate() {
+                let label = letter(index).to_string();
+                let label = if self.color {
+                    let (r, g, b) = colors[index];
+                    label.truecolor(r, g, b).bold().to_string()
+                } else {
+                    label
+                };
+
+                output.push_str(&format!(
+                    "{}: id={} length={} health={}{}\n",
+                    label,
+                    snake.id,
+                    snake.length(),
+                    snake.health,
+                    if Some(snake.id.as_str()) == you {
+                        " you"
+                    } else {
+                        ""
+                    }
+                ));
+            }
+        }
+
+        output
+    }
+}
+
+fn letter(index: usize) -> char {
+    (b'A' + (index % 26) as u8) as char
+}
+
+/// Each snake's own color where it has one no other snake shares, otherwise one from the palette.
+fn colors(snakes: &[Snake]) -> Vec<Rgb> {
+    let own: Vec<Option<Rgb>> = snakes
+        .iter()
+        .map(|snake| hex(&snake.customizations.color))
+        .collect();
+
+    own.iter()
+        .enumerate()
+        .map(|(index, color)| match color {
+            Some(color) if own.iter().filter(|other| *other == &Some(*color)).count() == 1 => {
+                *color
+            }
+            _ => PALETTE[index % PALETTE.len()],
+        })
+        .collect()
+}
+
+fn hex(color: &str) -> Option<Rgb> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> State {
+        "
+            turn: 7
+            ruleset: royale
+
+            . . . $ ~
+            . A . . ~
+            . a a B ~
+            . . . b ~
+
+            A: id=me health=80
+            B: id=them length=3
+        "
+        .parse()
+        .expect("must parse")
+    }
+
+    #[test]
+    fn plain() {
+        let state = state();
+        let renderer = Renderer {
+            candidates: vec![Move::Up, Move::Left],
+            chosen: Some(Move::Left),
+            ..Renderer::plain()
+        };
+        let rendered = renderer.render_state(&state);
+
+        assert_eq!(
+            rendered,
+            "turn: 7
+ruleset: royale
+
+3  . + . $ ~
+2  * A . . ~
+1  . a a B ~
+0  . . . b ~
+
+   0 1 2 3 4
+
+A: id=me length=3 health=80 you
+B: id=them length=3 health=100
+"
+        );
+
+        let parsed: State = Renderer::plain()
+            .render_state(&state)
+            .parse()
+            .expect("must parse back");
+        assert_eq!(parsed.turn, 7);
+        assert_eq!(parsed.you.id, "me");
+        assert_eq!(parsed.board.snakes[1].body, state.board.snakes[1].body);
+        assert_eq!(parsed.board.hazards.len(), 4);
+    }
+
+    #[test]
+    fn wide() {
+        let mut state = state();
+        state.board.width = 12;
+        state.board.height = 11;
+
+        let rendered = Renderer {
+            hazards: false,
+            ..Renderer::plain()
+        }
+        .render(&state.board);
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        assert_eq!(lines[0], "10   .  .  .  .  .  .  .  .  .  .  .  .");
+        assert_eq!(lines[8], " 2   .  A  .  .  .  .  .  .  .  .  .  .");
+        assert_eq!(lines[10], " 0   .  .  .  b  .  .  .  .  .  .  .  .");
+        assert_eq!(lines[12], "     0  1  2  3  4  5  6  7  8  9 10 11");
+        assert_eq!(lines[13], "");
+        assert_eq!(lines[14], "A: id=me length=3 health=80");
+    }
+
+    #[test]
+    fn colors() {
+        let mut snakes: Vec<Snake> = vec![Snake::default(), Snake::default(), Snake::default()];
+        snakes[0].customizations.color = "#FF0000".to_string();
+        // Shared, so neither gets it.
+        snakes[1].customizations.color = "#00ff00".to_string();
+        snakes[2].customizations.color = "#00FF00".to_string();
+
+        assert_eq!(
+            super::colors(&snakes),
+            vec![(255, 0, 0), PALETTE[1], PALETTE[2]]
+        );
+    }
+}
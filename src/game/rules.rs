@@ -0,0 +1,683 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use super::game::Mode;
+use super::mv::Move;
+use super::snake::{Snake, MAX_HEALTH};
+use super::state::State;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EliminationCause {
+    OutOfHealth,
+    OutOfBounds,
+    SelfCollision,
+    BodyCollision,
+    HeadCollision,
+    /// A squadmate was eliminated and the squad shares eliminations.
+    Squad,
+}
+
+impl fmt::Display for EliminationCause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            EliminationCause::OutOfHealth => "out of health",
+            EliminationCause::OutOfBounds => "out of bounds",
+            EliminationCause::SelfCollision => "self collision",
+            EliminationCause::BodyCollision => "body collision",
+            EliminationCause::HeadCollision => "head collision",
+            EliminationCause::Squad => "squad eliminated",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Elimination {
+    pub id: String,
+    pub cause: EliminationCause,
+    pub by: Option<String>,
+}
+
+impl State {
+    /// Advances the game by one turn, using each snake's current direction when no move is given.
+    pub fn apply_moves(&self, moves: &HashMap<String, Move>) -> State {
+        self.advance(moves).0
+    }
+
+    /// Like `apply_moves`, but also reports which snakes were eliminated this turn and why.
+    pub fn advance(&self, moves: &HashMap<String, Move>) -> (State, Vec<Elimination>) {
+        let mut next = self.clone();
+        next.turn += 1;
+
+        next.move_snakes(moves);
+        next.reduce_health();
+        next.damage_hazards();
+        next.feed_snakes();
+
+        let mut eliminations = next.eliminations();
+        next.share_squad_attributes(&mut eliminations);
+
+        let moved = next.board.snakes.clone();
+        next.board
+            .snakes
+            .retain(|snake| !eliminations.iter().any(|e| e.id == snake.id));
+
+        if let Some(you) = moved.into_iter().find(|snake| snake.id == self.you.id) {
+            next.you = you;
+        }
+
+        (next, eliminations)
+    }
+
+    /// Whether the game has finished: solo games run until the last snake dies, squad games until
+    /// at most one squad is left, and all others until at most one snake is left.
+    pub fn is_over(&self) -> bool {
+        match self.game.ruleset.mode() {
+            Mode::Solo => self.board.snakes.is_empty(),
+            Mode::Squad => match self.board.snakes.first() {
+                Some(first) => self
+                    .board
+                    .snakes
+                    .iter()
+                    .all(|snake| snake == first || snake.squadmate(first)),
+                None => true,
+            },
+            _ => self.board.snakes.len() <= 1,
+        }
+    }
+
+    /// Snakes without a body, which the engine never sends, stay where they are.
+    fn move_snakes(&mut self, moves: &HashMap<String, Move>) {
+        for index in 0..self.board.snakes.len() {
+            let snake = &self.board.snakes[index];
+            if snake.body.is_empty() {
+                continue;
+            }
+
+            let mv = moves
+                .get(&snake.id)
+                .copied()
+                .unwrap_or_else(|| snake.direction());
+            let head = self.board.shift(&snake.head, &mv);
+
+            let snake = &mut self.board.snakes[index];
+            snake.head = head;
+            snake.body.insert(0, head);
+            snake.body.pop();
+        }
+    }
+
+    fn reduce_health(&mut self) {
+        for snake in self.board.snakes.iter_mut() {
+            snake.health = snake.health.saturating_sub(1);
+        }
+    }
+
+    fn damage_hazards(&mut self) {
+        let damage = self.game.ruleset.settings.hazard_damage_per_turn;
+
+        for snake in self.board.snakes.iter_mut() {
+            if self.board.hazards.contains(&snake.head) && !self.board.food.contains(&snake.head) {
+                snake.health = snake.health.saturating_sub(damage);
+            }
+        }
+    }
+
+    fn feed_snakes(&mut self) {
+        // Constrictor snakes are fed every turn and never shrink.
+        if self.game.ruleset.mode() == Mode::Constrictor {
+            self.board.snakes.iter_mut().for_each(grow);
+            return;
+        }
+
+        let eaten: Vec<_> = self
+            .board
+            .food
+            .iter()
+            .filter(|food| self.board.snakes.iter().any(|snake| &snake.head == *food))
+            .copied()
+            .collect();
+
+        for snake in self.board.snakes.iter_mut() {
+            if eaten.contains(&snake.head) {
+                grow(snake);
+            }
+        }
+
+        self.board.food.retain(|food| !eaten.contains(food));
+    }
+
+    fn eliminations(&self) -> Vec<Elimination> {
+        let mut eliminations = Vec::new();
+
+        for snake in self.board.snakes.iter() {
+            let cause = if snake.health == 0 {
+                Some(EliminationCause::OutOfHealth)
+            } else if !self.board.in_bounds(&snake.head) {
+                Some(EliminationCause::OutOfBounds)
+            } else {
+                None
+            };
+
+            if let Some(cause) = cause {
+                eliminations.push(Elimination {
+                    id: snake.id.clone(),
+                    cause,
+                    by: None,
+                });
+            }
+        }
+
+        // Snakes already out of the game don't take part in collisions.
+        let contenders: Vec<&Snake> = self
+            .board
+            .snakes
+            .iter()
+            .filter(|snake| !eliminations.iter().any(|e| e.id == snake.id))
+            .collect();
+
+        let squad_bodies = self.game.ruleset.squad_body_collisions();
+        let mut collisions = Vec::new();
+        for snake in contenders.iter() {
+            if let Some(collision) = collision(snake, &contenders, squad_bodies) {
+                collisions.push(collision);
+            }
+        }
+
+        eliminations.extend(collisions);
+        eliminations
+    }
+
+    /// Evens out health and length across each squad and takes squadmates of eliminated snakes
+    /// down with them, as far as the squad settings ask for it.
+    fn share_squad_attributes(&mut self, eliminations: &mut Vec<Elimination>) {
+        if self.game.ruleset.mode() != Mode::Squad {
+            return;
+        }
+
+        let settings = self.game.ruleset.settings.squad.clone();
+        let snakes = self.board.snakes.clone();
+        let eliminated = |snake: &Snake, eliminations: &[Elimination]| {
+            eliminations.iter().any(|e| e.id == snake.id)
+        };
+
+        let mut squad_eliminations = Vec::new();
+        for snake in self.board.snakes.iter_mut() {
+            if eliminated(snake, eliminations) {
+                continue;
+            }
+
+            let squadmates: Vec<&Snake> = snakes
+                .iter()
+                .filter(|other| snake.squadmate(other))
+                .collect();
+
+            for other in squadmates {
+                if settings.shared_health {
+                    snake.health = snake.health.max(other.health);
+                }
+
+                if settings.shared_length && !snake.body.is_empty() {
+                    while snake.length() < other.length() {
+                        snake.body.push(*snake.tail());
+                    }
+                }
+
+                if settings.shared_elimination
+                    && eliminated(other, eliminations)
+                    && !squad_eliminations
+                        .iter()
+                        .any(|e: &Elimination| e.id == snake.id)
+                {
+                    squad_eliminations.push(Elimination {
+                        id: snake.id.clone(),
+                        cause: EliminationCause::Squad,
+                        by: Some(other.id.clone()),
+                    });
+                }
+            }
+        }
+
+        eliminations.extend(squad_eliminations);
+    }
+}
+
+fn grow(snake: &mut Snake) {
+    snake.health = MAX_HEALTH;
+    if let Some(tail) = snake.body.last() {
+        snake.body.push(*tail);
+    }
+}
+
+fn collision(snake: &Snake, snakes: &[&Snake], squad_bodies: bool) -> Option<Elimination> {
+    let eliminate = |cause, by: &Snake| {
+        Some(Elimination {
+            id: snake.id.clone(),
+            cause,
+            by: Some(by.id.clone()),
+        })
+    };
+
+    if snake.body.iter().skip(1).any(|point| *point == snake.head) {
+        return eliminate(EliminationCause::SelfCollision, snake);
+    }
+
+    for other in snakes
+        .iter()
+        .filter(|other| **other != snake && !(squad_bodies && snake.squadmate(other)))
+    {
+        if other.body.iter().skip(1).any(|point| *point == snake.head) {
+            return eliminate(EliminationCause::BodyCollision, other);
+        }
+    }
+
+    for other in snakes.iter().filter(|other| **other != snake) {
+        if other.head == snake.head && other.length() >= snake.length() {
+            return eliminate(EliminationCause::HeadCollision, other);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::point::Point;
+
+    use super::*;
+
+    /// Puts the snakes on the red squad, which pictures don't show.
+    fn red(state: &mut State, ids: &[&str]) {
+        let snakes = state.board.snakes.iter_mut().chain([&mut state.you]);
+        for snake in snakes.filter(|snake| ids.contains(&snake.id.as_str())) {
+            snake.squad = "red".to_string();
+        }
+    }
+
+    fn moves(pairs: &[(&str, Move)]) -> HashMap<String, Move> {
+        pairs.iter().map(|(id, mv)| (id.to_string(), *mv)).collect()
+    }
+
+    #[test]
+    fn movement() {
+        let state: State = "
+            turn: 5
+
+            6  . . . . . . .
+            5  . . . . . . .
+            4  . . . . . . .
+            3  . . . . . . .
+            2  . . A . . . .
+            1  . . a . . . .
+            0  . . a . . . .
+            A: health=50
+        "
+        .parse()
+        .expect("must parse");
+
+        let next = state.apply_moves(&moves(&[("a", Move::Right)]));
+
+        assert_eq!(next.turn, 6);
+        assert_eq!(next.you.head, Point::new(3, 2));
+        assert_eq!(
+            next.you.body,
+            vec![Point::new(3, 2), Point::new(2, 2), Point::new(2, 1)]
+        );
+        assert_eq!(next.you.health, 49);
+
+        // Without a move the snake keeps going the way it was heading.
+        let next = state.apply_moves(&HashMap::new());
+        assert_eq!(next.you.head, Point::new(2, 3));
+    }
+
+    #[test]
+    fn feeding() {
+        let state: State = "
+            6  . . . . . . .
+            5  . . . . . $ .
+            4  . . . . . . .
+            3  . . $ . . . .
+            2  . . A . . . .
+            1  . . a . . . .
+            0  . . . . . . .
+            A: health=50
+        "
+        .parse()
+        .expect("must parse");
+
+        let next = state.apply_moves(&moves(&[("a", Move::Up)]));
+
+        assert_eq!(next.you.health, MAX_HEALTH);
+        assert_eq!(
+            next.you.body,
+            vec![Point::new(2, 3), Point::new(2, 2), Point::new(2, 2)]
+        );
+        assert_eq!(next.board.food, vec![Point::new(5, 5)]);
+    }
+
+    #[test]
+    fn out_of_bounds_and_health() {
+        let state: State = "
+            6  . . . . . . .
+            5  . . . . . . .
+            4  . . . . B . .
+            3  . . . . b . .
+            2  A a . . . . .
+            1  . . . . . . .
+            0  . . . . . . .
+            B: health=1
+        "
+        .parse()
+        .expect("must parse");
+
+        let (next, eliminations) = state.advance(&moves(&[("a", Move::Left), ("b", Move::Up)]));
+
+        assert!(next.board.snakes.is_empty());
+        assert_eq!(
+            eliminations,
+            vec![
+                Elimination {
+                    id: "a".to_string(),
+                    cause: EliminationCause::OutOfBounds,
+                    by: None,
+                },
+                Elimination {
+                    id: "b".to_string(),
+                    cause: EliminationCause::OutOfHealth,
+                    by: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn collisions() {
+        let state: State = "
+            6  . . . . . . .
+            5  . . . . . . .
+            4  . . . . . . .
+            3  . . . . . . .
+            2  c C A . B . .
+            1  . . a . b . .
+            0  . . a . b . .
+        "
+        .parse()
+        .expect("must parse");
+
+        // a and b meet head to head with equal lengths.
+        let (next, eliminations) = state.advance(&moves(&[
+            ("a", Move::Right),
+            ("b", Move::Left),
+            ("c", Move::Down),
+        ]));
+
+        assert_eq!(next.board.snakes.len(), 1);
+        assert_eq!(next.board.snakes[0].id, "c");
+        assert_eq!(eliminations.len(), 2);
+        assert!(eliminations
+            .iter()
+            .all(|e| e.cause == EliminationCause::HeadCollision));
+
+        // c runs into a's neck.
+        let (next, eliminations) = state.advance(&moves(&[
+            ("a", Move::Up),
+            ("b", Move::Up),
+            ("c", Move::Right),
+        ]));
+
+        assert_eq!(next.board.snakes.len(), 2);
+        assert_eq!(
+            eliminations,
+            vec![Elimination {
+                id: "c".to_string(),
+                cause: EliminationCause::BodyCollision,
+                by: Some("a".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn chasing_tail() {
+        let state: State = "
+            6  . . . . . . .
+            5  . . . . . . .
+            4  . . . . . . .
+            3  . . . . . . .
+            2  . . A a . . .
+            1  . . a a . . .
+            0  . . . . . . .
+        "
+        .parse()
+        .expect("must parse");
+
+        let (_, eliminations) = state.advance(&moves(&[("a", Move::Right)]));
+        assert!(eliminations.is_empty());
+
+        let (_, eliminations) = state.advance(&moves(&[("a", Move::Down)]));
+        assert_eq!(eliminations[0].cause, EliminationCause::SelfCollision);
+    }
+
+    #[test]
+    fn empty_body() {
+        let mut state: State = "
+            6  . . . . . . .
+            5  . . . . . . .
+            4  . . . . B . .
+            3  . . . . . . .
+            2  . . A . . . .
+            1  . . a . . . .
+            0  . . . . . . .
+            B: health=50
+        "
+        .parse()
+        .expect("must parse");
+        state.board.snakes[1].body.clear();
+
+        let (next, eliminations) = state.advance(&moves(&[("a", Move::Up)]));
+        assert!(eliminations.is_empty());
+        assert_eq!(next.board.snakes.len(), 2);
+        assert!(next.board.snakes[1].body.is_empty());
+
+        // Its head on food, it eats without growing a body.
+        state.board.food = vec![Point::new(4, 4)];
+        let next = state.apply_moves(&moves(&[("a", Move::Up)]));
+        assert_eq!(next.board.snakes[1].health, MAX_HEALTH);
+        assert!(next.board.snakes[1].body.is_empty());
+        assert!(next.board.food.is_empty());
+
+        // Constrictor snakes grow every turn.
+        state.board.food.clear();
+        state.game.ruleset.name = "constrictor".to_string();
+        let next = state.apply_moves(&moves(&[("a", Move::Up)]));
+        assert!(next.board.snakes[1].body.is_empty());
+        assert_eq!(next.board.snakes[0].length(), 3);
+    }
+
+    #[test]
+    fn hazards() {
+        let mut state: State = "
+            6  . . . . . . .
+            5  . . . . . . .
+            4  . . . . . . .
+            3  . . ~ . . . .
+            2  . . A ~ . . .
+            1  . . a . . . .
+            0  . . . . . . .
+            A: health=50
+        "
+        .parse()
+        .expect("must parse");
+        state.board.food.push(Point::new(3, 2));
+
+        let next = state.apply_moves(&moves(&[("a", Move::Up)]));
+        assert_eq!(next.you.health, 35);
+
+        // Food inside a hazard still refills health.
+        let next = state.apply_moves(&moves(&[("a", Move::Right)]));
+        assert_eq!(next.you.health, MAX_HEALTH);
+    }
+
+    #[test]
+    fn wrapped() {
+        let state: State = "
+            ruleset: wrapped
+
+            6  A a . . . . .
+            5  . . . . . . .
+            4  . . . . . . .
+            3  . . . . . . .
+            2  . . . . . . .
+            1  . . . . . . .
+            0  . . . . . . .
+        "
+        .parse()
+        .expect("must parse");
+
+        let (next, eliminations) = state.advance(&moves(&[("a", Move::Left)]));
+        assert!(eliminations.is_empty());
+        assert_eq!(next.you.head, Point::new(6, 6));
+
+        let (next, eliminations) = state.advance(&moves(&[("a", Move::Up)]));
+        assert!(eliminations.is_empty());
+        assert_eq!(next.you.head, Point::new(0, 0));
+    }
+
+    #[test]
+    fn constrictor() {
+        let state: State = "
+            ruleset: constrictor
+
+            6  . . . . . . .
+            5  . . . . . . .
+            4  . . . . . . .
+            3  . . . . . . .
+            2  . . A . . . .
+            1  . . a . . . .
+            0  . . . . . . .
+            A: health=50
+        "
+        .parse()
+        .expect("must parse");
+        let next = state.apply_moves(&moves(&[("a", Move::Up)]));
+        let next = next.apply_moves(&moves(&[("a", Move::Up)]));
+
+        assert_eq!(next.you.health, MAX_HEALTH);
+        assert_eq!(next.you.length(), 4);
+    }
+
+    #[test]
+    fn is_over() {
+        let mut state: State = "
+            6  . . . . . . .
+            5  . . . . . . .
+            4  . . . . . . .
+            3  . . . . . . .
+            2  . . A . . . .
+            1  . . a . . . .
+            0  . . . . . . .
+        "
+        .parse()
+        .expect("must parse");
+
+        assert!(state.is_over());
+
+        state.game.ruleset.name = "solo".to_string();
+        assert!(!state.is_over());
+
+        state.board.snakes.clear();
+        assert!(state.is_over());
+    }
+
+    #[test]
+    fn squad() {
+        let mut state: State = "
+            ruleset: squad
+
+            6  . . . . . . .
+            5  . . . . . . .
+            4  . . . . . . .
+            3  . . B . . . .
+            2  . . b C c c .
+            1  a A b . . . .
+            0  . . b . . . .
+        "
+        .parse()
+        .expect("must parse");
+        red(&mut state, &["a", "b"]);
+        state.game.ruleset.settings.squad.allow_body_collisions = true;
+
+        let turn = moves(&[("a", Move::Right), ("b", Move::Up), ("c", Move::Left)]);
+        let (next, eliminations) = state.advance(&turn);
+
+        assert_eq!(
+            eliminations,
+            vec![Elimination {
+                id: "c".to_string(),
+                cause: EliminationCause::BodyCollision,
+                by: Some("b".to_string()),
+            }]
+        );
+        assert!(next.is_over());
+
+        state.game.ruleset.settings.squad.allow_body_collisions = false;
+        let (next, eliminations) = state.advance(&turn);
+
+        assert_eq!(eliminations.len(), 2);
+        assert!(next.is_over());
+    }
+
+    #[test]
+    fn squad_sharing() {
+        let mut state: State = "
+            ruleset: squad
+
+            6  . . . . . . .
+            5  . . . . . . C
+            4  . . . . . . c
+            3  . . . . B . .
+            2  . . . . b . .
+            1  . A . . b . .
+            0  . a . . . . .
+            A: health=20
+            B: health=80
+            C: health=90
+        "
+        .parse()
+        .expect("must parse");
+        red(&mut state, &["a", "b"]);
+
+        let turn = moves(&[("a", Move::Up), ("b", Move::Up), ("c", Move::Left)]);
+
+        let next = state.apply_moves(&turn);
+        assert_eq!(next.you.health, 19);
+        assert_eq!(next.you.length(), 2);
+
+        state.game.ruleset.settings.squad.shared_health = true;
+        state.game.ruleset.settings.squad.shared_length = true;
+        let next = state.apply_moves(&turn);
+        assert_eq!(next.you.health, 79);
+        assert_eq!(
+            next.you.body,
+            vec![Point::new(1, 2), Point::new(1, 1), Point::new(1, 1)]
+        );
+
+        // b runs out of health, and a goes with it.
+        state.board.snakes[1].health = 1;
+        state.game.ruleset.settings.squad.shared_health = false;
+        let (next, eliminations) = state.advance(&turn);
+        assert_eq!(eliminations.len(), 1);
+        assert_eq!(next.board.snakes.len(), 2);
+
+        state.game.ruleset.settings.squad.shared_elimination = true;
+        let (next, eliminations) = state.advance(&turn);
+        assert_eq!(
+            eliminations[1],
+            Elimination {
+                id: "a".to_string(),
+                cause: EliminationCause::Squad,
+                by: Some("b".to_string()),
+            }
+        );
+        assert_eq!(next.board.snakes.len(), 1);
+        assert!(next.is_over());
+    }
+}
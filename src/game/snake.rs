@@ -1,24 +1,68 @@
+use super::mv::Move;
 use super::point::Point;
-use serde::Deserialize;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
 
-#[derive(Deserialize, Debug, Clone)]
+pub const MAX_HEALTH: u16 = 100;
+
+/// `length` isn't stored since it's always the length of `body`, which is what gets serialized.
+#[derive(Deserialize, Debug, Clone, Default)]
 pub struct Snake {
     pub id: String,
+    #[serde(default)]
+    pub name: String,
     pub health: u16,
     pub body: Vec<Point>,
+    /// Round trip time of the snake's last move, in milliseconds. The engine sends this as a
+    /// string, which is empty or zero before the first move or after a timeout.
+    #[serde(default, deserialize_with = "latency::deserialize")]
+    pub latency: u32,
     pub head: Point,
-    // shout: String,
+    #[serde(default)]
+    pub shout: String,
+    #[serde(default)]
+    pub squad: String,
+    #[serde(default)]
+    pub customizations: Customizations,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct Customizations {
+    pub color: String,
+    pub head: String,
+    pub tail: String,
 }
 
 impl Snake {
     pub fn length(&self) -> usize {
         self.body.len()
     }
-    
+
+    pub fn neck(&self) -> Option<&Point> {
+        self.body.get(1)
+    }
+
+    /// The direction the snake last moved in, defaulting to up while its body is still stacked.
+    pub fn direction(&self) -> Move {
+        self.neck()
+            .and_then(|neck| {
+                Move::all()
+                    .into_iter()
+                    .find(|mv| neck.shift(mv) == self.head)
+            })
+            .unwrap_or(Move::Up)
+    }
+
     pub fn tail(&self) -> &Point {
         self.body.last().expect("snake with no tail")
     }
 
+    /// Whether `other` is a different snake on the same squad.
+    pub fn squadmate(&self, other: &Snake) -> bool {
+        !self.squad.is_empty() && self.squad == other.squad && self != other
+    }
+
     pub fn at(&self, point: &Point, ignore_tail: bool) -> bool {
         if ignore_tail && point == self.tail() {
             false
@@ -28,6 +72,42 @@ impl Snake {
     }
 }
 
+impl Serialize for Snake {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut snake = serializer.serialize_struct("Snake", 10)?;
+        snake.serialize_field("id", &self.id)?;
+        snake.serialize_field("name", &self.name)?;
+        snake.serialize_field("health", &self.health)?;
+        snake.serialize_field("body", &self.body)?;
+        snake.serialize_field("latency", &self.latency.to_string())?;
+        snake.serialize_field("head", &self.head)?;
+        snake.serialize_field("length", &self.length())?;
+        snake.serialize_field("shout", &self.shout)?;
+        snake.serialize_field("squad", &self.squad)?;
+        snake.serialize_field("customizations", &self.customizations)?;
+        snake.end()
+    }
+}
+
+mod latency {
+    use serde::{de, Deserialize, Deserializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Latency {
+        Number(u32),
+        Text(String),
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+        match Latency::deserialize(deserializer)? {
+            Latency::Number(latency) => Ok(latency),
+            Latency::Text(text) if text.trim().is_empty() => Ok(0),
+            Latency::Text(text) => text.trim().parse().map_err(de::Error::custom),
+        }
+    }
+}
+
 impl PartialEq for Snake {
     fn eq(&self, other: &Self) -> bool {
         self.id == other.id
@@ -51,6 +131,7 @@ mod tests {
                 Point { x: 2, y: 3 },
                 Point { x: 2, y: 3 },
             ],
+            ..Default::default()
         };
 
         // Head
@@ -69,4 +150,61 @@ mod tests {
         assert_eq!(snake.at(&Point { x: 0, y: 0 }, false), false);
         assert_eq!(snake.at(&Point { x: 0, y: 0 }, true), false);
     }
+
+    #[test]
+    fn direction() {
+        let mut snake = Snake {
+            id: "a".to_string(),
+            health: 0,
+            head: Point { x: 1, y: 1 },
+            body: vec![Point { x: 1, y: 1 }, Point { x: 1, y: 2 }],
+            ..Default::default()
+        };
+
+        assert_eq!(snake.direction(), Move::Down);
+
+        snake.body = vec![Point { x: 1, y: 1 }, Point { x: 1, y: 1 }];
+        assert_eq!(snake.direction(), Move::Up);
+    }
+
+    #[test]
+    fn deserialize() {
+        let minimal: Snake = serde_json::from_str(
+            r#"{"id": "a", "health": 90, "body": [{"x": 1, "y": 1}], "head": {"x": 1, "y": 1}}"#,
+        )
+        .expect("must parse");
+
+        assert_eq!(minimal.latency, 0);
+        assert_eq!(minimal.squad, "");
+        assert_eq!(minimal.customizations, Customizations::default());
+
+        let full: Snake = serde_json::from_str(
+            r##"{
+                "id": "b",
+                "name": "b",
+                "health": 90,
+                "body": [{"x": 1, "y": 1}],
+                "head": {"x": 1, "y": 1},
+                "latency": "123",
+                "length": 1,
+                "shout": "",
+                "squad": "red",
+                "customizations": {"color": "#000000"}
+            }"##,
+        )
+        .expect("must parse");
+
+        assert_eq!(full.latency, 123);
+        assert_eq!(full.customizations.color, "#000000");
+
+        let numeric: Snake = serde_json::from_str(
+            r#"{"id": "c", "health": 90, "body": [], "head": {"x": 1, "y": 1}, "latency": 45, "squad": "red"}"#,
+        )
+        .expect("must parse");
+
+        assert_eq!(numeric.latency, 45);
+        assert!(numeric.squadmate(&full));
+        assert!(!numeric.squadmate(&numeric));
+        assert!(!minimal.squadmate(&Snake::default()));
+    }
 }
@@ -0,0 +1,158 @@
+use rand::{seq::SliceRandom, Rng};
+
+use super::game::Mode;
+use super::point::Point;
+use super::state::State;
+
+impl State {
+    /// Places new food and, in royale games, grows the hazards, as the engine does at the end of
+    /// each turn.
+    pub fn spawn<R: Rng>(&mut self, rng: &mut R) {
+        self.spawn_food(rng);
+        self.shrink(rng);
+    }
+
+    fn spawn_food<R: Rng>(&mut self, rng: &mut R) {
+        if self.game.ruleset.mode() == Mode::Constrictor {
+            return;
+        }
+
+        let settings = &self.game.ruleset.settings;
+        let needed = if self.board.food.len() < settings.minimum_food as usize {
+            settings.minimum_food as usize - self.board.food.len()
+        } else if rng.gen_range(0..100) < settings.food_spawn_chance {
+            1
+        } else {
+            0
+        };
+
+        if needed == 0 {
+            return;
+        }
+
+        let grid = self.board.grid();
+        let free: Vec<Point> = self
+            .points()
+            .filter(|point| !grid.occupied(point) && !grid.food_at(point))
+            .collect();
+
+        self.board
+            .food
+            .extend(free.choose_multiple(rng, needed).copied());
+    }
+
+    /// Every few turns of a royale game, fills the outermost safe row or column on a random side
+    /// with hazards.
+    fn shrink<R: Rng>(&mut self, rng: &mut R) {
+        let every = self.game.ruleset.settings.royale.shrink_every_n_turns;
+        if self.game.ruleset.mode() != Mode::Royale
+            || every == 0
+            || self.turn == 0
+            || !self.turn.is_multiple_of(every)
+        {
+            return;
+        }
+
+        let grid = self.board.grid();
+        let safe: Vec<Point> = self.points().filter(|p| !grid.hazard_at(p)).collect();
+
+        let (min_x, max_x, min_y, max_y) = match (
+            safe.iter().map(|p| p.x).min(),
+            safe.iter().map(|p| p.x).max(),
+            safe.iter().map(|p| p.y).min(),
+            safe.iter().map(|p| p.y).max(),
+        ) {
+            (Some(min_x), Some(max_x), Some(min_y), Some(max_y)) => (min_x, max_x, min_y, max_y),
+            _ => return,
+        };
+
+        let line: Vec<Point> = match rng.gen_range(0..4) {
+            0 => (min_y..=max_y).map(|y| Point::new(min_x, y)).collect(),
+            1 => (min_y..=max_y).map(|y| Point::new(max_x, y)).collect(),
+            2 => (min_x..=max_x).map(|x| Point::new(x, min_y)).collect(),
+            _ => (min_x..=max_x).map(|x| Point::new(x, max_y)).collect(),
+        };
+
+        self.board
+            .hazards
+            .extend(line.into_iter().filter(|p| !grid.hazard_at(p)));
+    }
+
+    fn points(&self) -> impl Iterator<Item = Point> {
+        let (width, height) = (self.board.width, self.board.height);
+        (0..width).flat_map(move |x| (0..height).map(move |y| Point::new(x, y)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::game::game::Settings;
+
+    use super::*;
+
+    fn state(ruleset: &str) -> State {
+        let mut state: State = format!(
+            "
+            turn: 25
+            ruleset: {}
+
+            4  . . . . .
+            3  . . . . .
+            2  . . . . .
+            1  . A . . .
+            0  . a . . .
+            ",
+            ruleset
+        )
+        .parse()
+        .expect("must parse");
+
+        state.game.ruleset.settings = Settings {
+            minimum_food: 3,
+            food_spawn_chance: 0,
+            ..Settings::default()
+        };
+        state
+    }
+
+    #[test]
+    fn spawn_food() {
+        let mut state = state("standard");
+        state.spawn(&mut StdRng::seed_from_u64(1));
+
+        assert_eq!(state.board.food.len(), 3);
+        assert!(state
+            .board
+            .food
+            .iter()
+            .all(|food| state.board.snake_at(food).is_none()));
+        assert!(state.board.hazards.is_empty());
+
+        // Nothing more spawns once the minimum is met and the spawn chance is zero.
+        state.spawn(&mut StdRng::seed_from_u64(1));
+        assert_eq!(state.board.food.len(), 3);
+    }
+
+    #[test]
+    fn shrink() {
+        // Not on the first turn, only every 25 after.
+        let mut state = state("royale");
+        state.turn = 0;
+        state.spawn(&mut StdRng::seed_from_u64(1));
+        assert!(state.board.hazards.is_empty());
+
+        state.turn = 25;
+        state.spawn(&mut StdRng::seed_from_u64(1));
+        assert_eq!(state.board.hazards.len(), 5);
+
+        state.turn = 26;
+        state.spawn(&mut StdRng::seed_from_u64(1));
+        assert_eq!(state.board.hazards.len(), 5);
+
+        state.turn = 50;
+        state.spawn(&mut StdRng::seed_from_u64(1));
+        assert!(state.board.hazards.len() >= 9);
+    }
+}
@@ -1,14 +1,13 @@
 use super::board::Board;
-use super::game::Game;
+use super::game::{Game, Mode};
 use super::mv::Move;
 use super::point::Point;
 use super::snake::Snake;
 
-use anyhow::Result;
-use rand::{seq::SliceRandom, thread_rng};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(from = "StateData")]
 pub struct State {
     pub game: Game,
     pub turn: u16,
@@ -16,148 +15,49 @@ pub struct State {
     pub you: Snake,
 }
 
-impl State {
-    fn process<F>(&self, process: &str, moves: Vec<Move>, f: F) -> Vec<Move>
-    where
-        F: Fn(Point) -> bool,
-    {
-        let before = moves.clone();
-
-        let after: Vec<Move> = moves
-            .into_iter()
-            .filter(|mv| f(self.you.head.shift(&mv)))
-            .collect();
-
-        if after.is_empty() {
-            println!(
-                "game {}, turn {}, {}: skipping because empty",
-                self.game.id, self.turn, process
-            );
-            before
-        } else if before == after {
-            println!(
-                "game {}, turn {}, {}: no changes",
-                self.game.id, self.turn, process
-            );
-            after
-        } else {
-            println!(
-                "game {}, turn {}, {}: {:?} -> {:?}",
-                self.game.id, self.turn, process, before, after
-            );
-            after
-        }
-    }
-
-    pub fn decide(&self, hunger_coefficient: f32) -> Result<(Move, String)> {
-        let mut moves = Move::all();
-
-        moves = self.process("in bounds", moves, |point| self.board.in_bounds(&point));
-        moves = self.process("snake collisions", moves, |point| {
-            !self
-                .board
-                .snakes
-                .iter()
-                .any(|snake| snake.at(&point, snake != &self.you || self.turn > 2))
-        });
-
-        moves = self.process("threatened", moves, |point| !self.threatened(&point));
-        moves = self.process("hazards", moves, |point| !self.board.hazard_at(&point));
-
-        let pocket_sizes = self.board.pocket_sizes();
-        let largest = moves
-            .iter()
-            .map(|mv| {
-                pocket_sizes
-                    .get(&self.you.head.shift(&mv))
-                    .unwrap_or(&0usize)
-            })
-            .max();
-
-        if let Some(size) = largest {
-            moves = self.process("select largest pocket", moves, |point| {
-                pocket_sizes.get(&point).unwrap_or(&0usize) == size
-            });
-        }
-
-        if let Some(closest_food) = self.board.closest_food(&self.you.head) {
-            let distance = closest_food.distance(&self.you.head);
-
-            if self.need_food(distance, hunger_coefficient) || self.compete_for_biggest() {
-                moves = self.process("food moves", moves, |point| {
-                    closest_food.distance(&point) < distance
-                });
-            }
-        }
-
-        moves = self.process("kill moves", moves, |point| self.kill_chance(&point));
-
-        let closest_smaller_snake = self
-            .board
-            .snakes
-            .iter()
-            .filter(|snake| snake.length() < self.you.length())
-            .min_by(|snake_a, snake_b| {
-                let distance_a = self.you.head.distance(&snake_a.head);
-                let distance_b = self.you.head.distance(&snake_b.head);
-
-                distance_a.cmp(&distance_b)
-            });
+#[derive(Deserialize)]
+struct StateData {
+    game: Game,
+    turn: u16,
+    board: Board,
+    you: Snake,
+}
 
-        if let Some(snake) = closest_smaller_snake {
-            moves = self.process("seek kill", moves, |point| {
-                let current_distance = self.you.head.distance(&snake.head);
-                let new_distance = point.distance(&snake.head);
+impl From<StateData> for State {
+    fn from(data: StateData) -> Self {
+        let mut board = data.board;
+        board.wrapped = data.game.ruleset.mode() == Mode::Wrapped;
 
-                new_distance < current_distance
-            });
+        State {
+            game: data.game,
+            turn: data.turn,
+            board,
+            you: data.you,
         }
-
-        moves = self.process("circle", moves, |point| {
-            let tail = self.you.tail();
-            let current_distance = self.you.head.distance(&tail);
-            let new_distance = point.distance(&tail);
-
-            new_distance < current_distance
-        });
-
-        println!(
-            "game {}, turn {}, selecting move from {:?}",
-            self.game.id, self.turn, moves
-        );
-
-        moves.shuffle(&mut thread_rng());
-        let mv = moves.get(0).expect("failed to get move");
-
-        let shout = if self.board.food_at(&self.you.head.shift(mv)) {
-            "gulp"
-        } else {
-            ""
-        };
-
-        Ok((*mv, shout.to_string()))
     }
+}
 
-    fn need_food(&self, distance: i16, hunger_coefficient: f32) -> bool {
+impl State {
+    pub fn need_food(&self, distance: i16, hunger_coefficient: f32) -> bool {
         self.you.health < 10 || distance as f32 > self.you.health as f32 * hunger_coefficient
     }
 
-    fn compete_for_biggest(&self) -> bool {
+    pub fn compete_for_biggest(&self) -> bool {
         let biggest = self
             .board
             .snakes
             .iter()
-            .filter(|snake| *snake != &self.you)
+            .filter(|snake| *snake != &self.you && !snake.squadmate(&self.you))
             .map(|snake| snake.length())
             .max();
 
         biggest.is_some() && self.you.length() <= biggest.unwrap()
     }
 
-    fn threatened(&self, point: &Point) -> bool {
+    pub fn threatened(&self, point: &Point) -> bool {
         Move::all()
             .iter()
-            .map(|mv| point.shift(mv))
+            .map(|mv| self.board.shift(point, mv))
             .filter(|point| self.board.in_bounds(point))
             .filter(|point| point != &self.you.head)
             .filter(|point| {
@@ -165,6 +65,7 @@ impl State {
                     .snakes
                     .iter()
                     .filter(|snake| snake.length() >= self.you.length())
+                    .filter(|snake| !snake.squadmate(&self.you))
                     .any(|snake| point == &snake.head)
             })
             .peekable()
@@ -172,10 +73,10 @@ impl State {
             .is_some()
     }
 
-    fn kill_chance(&self, point: &Point) -> bool {
+    pub fn kill_chance(&self, point: &Point) -> bool {
         Move::all()
             .iter()
-            .map(|mv| point.shift(mv))
+            .map(|mv| self.board.shift(point, mv))
             .filter(|point| self.board.in_bounds(point))
             .filter(|point| point != &self.you.head)
             .filter(|point| {
@@ -183,6 +84,7 @@ impl State {
                     .snakes
                     .iter()
                     .filter(|snake| snake.length() < self.you.length())
+                    .filter(|snake| !snake.squadmate(&self.you))
                     .any(|snake| point == &snake.head)
             })
             .peekable()
@@ -193,80 +95,41 @@ impl State {
 
 #[cfg(test)]
 mod tests {
-    use crate::game::game::Ruleset;
-
     use super::*;
 
     #[test]
     fn threatened() {
-        let you = Snake {
-            id: "you".to_string(),
-            health: 0,
-            body: vec![Point::new(7, 3), Point::new(8, 3), Point::new(8, 4)],
-            head: Point::new(7, 3),
-        };
-
-        let snakes = vec![
-            you.clone(),
-            Snake {
-                id: "Big A".to_string(),
-                health: 0,
-                body: vec![
-                    Point::new(0, 0),
-                    Point::new(0, 1),
-                    Point::new(0, 2),
-                    Point::new(0, 3),
-                ],
-                head: Point::new(0, 0),
-            },
-            Snake {
-                id: "Same B".to_string(),
-                health: 0,
-                body: vec![Point::new(3, 2), Point::new(3, 3), Point::new(3, 4)],
-                head: Point::new(3, 2),
-            },
-            Snake {
-                id: "Lil C".to_string(),
-                health: 0,
-                body: vec![Point::new(2, 8), Point::new(2, 9)],
-                head: Point::new(2, 8),
-            },
-        ];
-
-        let board = Board {
-            height: 10,
-            width: 10,
-            food: vec![Point::new(7, 7)],
-            hazards: vec![],
-            snakes,
-        };
-
-        let state = State {
-            game: Game {
-                id: "asdf".to_string(),
-                ruleset: Ruleset {
-                    name: "standard".to_string(),
-                },
-            },
-            turn: 0,
-            you: you.clone(),
-            board,
-        };
+        let state: State = "
+            9  . . d . . . . . . .
+            8  . . D . . . . . . .
+            7  . . . . . . . $ . .
+            6  . . . . . . . . . .
+            5  . . . . . . . . . .
+            4  . . . c . . . . a .
+            3  b . . c . . . A a .
+            2  b . . C . . . . . .
+            1  b . . . . . . . . .
+            0  B . . . . . . . . .
+
+            A: id=you
+        "
+        .parse()
+        .expect("must parse");
 
         // You
         assert_eq!(state.threatened(&Point::new(6, 3)), false);
         assert_eq!(state.threatened(&Point::new(7, 2)), false);
         assert_eq!(state.threatened(&Point::new(7, 4)), false);
 
-        // Big A, corner
+        // B, corner
         assert_eq!(state.threatened(&Point::new(1, 0)), true);
 
-        // Same B
+        // C, same length
         assert_eq!(state.threatened(&Point::new(2, 2)), true);
         assert_eq!(state.threatened(&Point::new(3, 1)), true);
         assert_eq!(state.threatened(&Point::new(4, 2)), true);
 
-        // Lil C
+        // D, shorter
         assert_eq!(state.threatened(&Point::new(1, 8)), false);
         assert_eq!(state.threatened(&Point::new(2, 7)), false);
         assert_eq!(state.threatened(&Point::new(3, 8)), false);
@@ -274,4 +137,95 @@ mod tests {
         // Elsewhere
         assert_eq!(state.threatened(&Point::new(4, 4)), false);
     }
+
+    /// The example move request from the API docs, field for field.
+    const REQUEST: &str = r##"{
+        "game": {
+            "id": "totally-unique-game-id",
+            "ruleset": {
+                "name": "standard",
+                "version": "v1.2.3",
+                "settings": {
+                    "foodSpawnChance": 25,
+                    "minimumFood": 1,
+                    "hazardDamagePerTurn": 14,
+                    "hazardMap": "",
+                    "hazardMapAuthor": "",
+                    "royale": { "shrinkEveryNTurns": 5 },
+                    "squad": {
+                        "allowBodyCollisions": true,
+                        "sharedElimination": true,
+                        "sharedHealth": true,
+                        "sharedLength": true
+                    }
+                }
+            },
+            "map": "standard",
+            "source": "league",
+            "timeout": 500
+        },
+        "turn": 14,
+        "board": {
+            "height": 11,
+            "width": 11,
+            "food": [{ "x": 5, "y": 5 }, { "x": 9, "y": 0 }, { "x": 2, "y": 6 }],
+            "hazards": [{ "x": 3, "y": 2 }],
+            "snakes": [
+                {
+                    "id": "snake-508e96ac-94ad-11ea-bb37",
+                    "name": "My Snake",
+                    "health": 54,
+                    "body": [{ "x": 0, "y": 0 }, { "x": 1, "y": 0 }, { "x": 2, "y": 0 }],
+                    "latency": "111",
+                    "head": { "x": 0, "y": 0 },
+                    "length": 3,
+                    "shout": "why are we shouting??",
+                    "squad": "",
+                    "customizations": { "color": "#FF0000", "head": "pixel", "tail": "pixel" }
+                },
+                {
+                    "id": "snake-b67f4906-94ae-11ea-bb37",
+                    "name": "Another Snake",
+                    "health": 16,
+                    "body": [
+                        { "x": 5, "y": 4 },
+                        { "x": 5, "y": 3 },
+                        { "x": 6, "y": 3 },
+                        { "x": 6, "y": 2 }
+                    ],
+                    "latency": "222",
+                    "head": { "x": 5, "y": 4 },
+                    "length": 4,
+                    "shout": "I'm not really sure...",
+                    "squad": "",
+                    "customizations": { "color": "#26CF04", "head": "silly", "tail": "curled" }
+                }
+            ]
+        },
+        "you": {
+            "id": "snake-508e96ac-94ad-11ea-bb37",
+            "name": "My Snake",
+            "health": 54,
+            "body": [{ "x": 0, "y": 0 }, { "x": 1, "y": 0 }, { "x": 2, "y": 0 }],
+            "latency": "111",
+            "head": { "x": 0, "y": 0 },
+            "length": 3,
+            "shout": "why are we shouting??",
+            "squad": "",
+            "customizations": { "color": "#FF0000", "head": "pixel", "tail": "pixel" }
+        }
+    }"##;
+
+    #[test]
+    fn round_trip() {
+        let request: serde_json::Value = serde_json::from_str(REQUEST).expect("must parse");
+        let state: State = serde_json::from_str(REQUEST).expect("must parse");
+
+        assert_eq!(state.game.source, "league");
+        assert_eq!(state.you.customizations.head, "pixel");
+        assert_eq!(
+            serde_json::to_value(&state).expect("must serialize"),
+            request
+        );
+    }
 }
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+
+use super::board::Board;
+use super::mv::Move;
+use super::point::Point;
+
+/// Which snake controls each free cell, found by growing every snake's reach outwards from its
+/// head one step at a time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Territory {
+    pub owners: HashMap<Point, String>,
+    pub sizes: HashMap<String, usize>,
+    pub food: HashMap<String, Vec<Point>>,
+}
+
+impl Territory {
+    pub fn size(&self, id: &str) -> usize {
+        self.sizes.get(id).copied().unwrap_or(0)
+    }
+
+    pub fn food(&self, id: &str) -> &[Point] {
+        self.food.get(id).map(|food| food.as_slice()).unwrap_or(&[])
+    }
+}
+
+impl Board {
+    /// Assigns each free cell to the snake whose head reaches it first. When several snakes
+    /// arrive on the same turn the longest one wins, and cells tied between equally long snakes
+    /// belong to no one.
+    pub fn territory(&self) -> Territory {
+        // Each claimed cell maps to the turn it was reached, the index of its owner if any, and
+        // the length of the longest snake that reached it on that turn.
+        let grid = self.grid();
+        let mut claims: HashMap<Point, (usize, Option<usize>, usize)> = HashMap::new();
+        let mut frontier: Vec<Point> = Vec::new();
+
+        for (index, snake) in self.snakes.iter().enumerate() {
+            claims.insert(snake.head, (0, Some(index), snake.length()));
+            frontier.push(snake.head);
+        }
+
+        let mut turn = 0;
+        while !frontier.is_empty() {
+            turn += 1;
+            let mut next: Vec<Point> = Vec::new();
+
+            for point in frontier.iter() {
+                let owner = match claims.get(point) {
+                    Some((_, Some(owner), _)) => *owner,
+                    _ => continue,
+                };
+                let length = self.snakes[owner].length();
+
+                for mv in Move::all() {
+                    let neighbor = self.shift(point, &mv);
+                    if !self.in_bounds(&neighbor) || grid.occupied(&neighbor) {
+                        continue;
+                    }
+
+                    match claims.get(&neighbor).copied() {
+                        None => {
+                            claims.insert(neighbor, (turn, Some(owner), length));
+                            next.push(neighbor);
+                        }
+                        Some((reached, current, longest)) if reached == turn => {
+                            if length > longest {
+                                claims.insert(neighbor, (turn, Some(owner), length));
+                            } else if length == longest && current != Some(owner) {
+                                claims.insert(neighbor, (turn, None, length));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            frontier = next;
+        }
+
+        let mut territory = Territory::default();
+        for (point, (turn, owner, _)) in claims {
+            let id = match owner {
+                Some(owner) if turn > 0 => self.snakes[owner].id.clone(),
+                _ => continue,
+            };
+
+            if grid.food_at(&point) {
+                territory.food.entry(id.clone()).or_default().push(point);
+            }
+
+            *territory.sizes.entry(id.clone()).or_default() += 1;
+            territory.owners.insert(point, id);
+        }
+
+        territory
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::state::State;
+
+    use super::*;
+
+    #[test]
+    fn territory() {
+        let mut board = "
+            2  $ . . . .
+            1  a . . . b
+            0  A . . . B
+        "
+        .parse::<State>()
+        .expect("must parse")
+        .board;
+        board.food.push(Point::new(4, 0));
+
+        let territory = board.territory();
+
+        // The middle column is equally far from two equally long snakes.
+        assert_eq!(territory.owners.get(&Point::new(2, 0)), None);
+        assert_eq!(territory.owners.get(&Point::new(2, 2)), None);
+        assert_eq!(
+            territory.owners.get(&Point::new(1, 2)),
+            Some(&"a".to_string())
+        );
+        assert_eq!(
+            territory.owners.get(&Point::new(3, 0)),
+            Some(&"b".to_string())
+        );
+
+        assert_eq!(territory.size("a"), 4);
+        assert_eq!(territory.size("b"), 4);
+        assert_eq!(territory.food("a"), &[Point::new(0, 2)]);
+
+        // Food under a head isn't up for grabs.
+        assert!(territory.food("b").is_empty());
+    }
+
+    #[test]
+    fn longer_snake_wins_ties() {
+        let board = "
+            A . . . B
+            B: length=2
+        "
+        .parse::<State>()
+        .expect("must parse")
+        .board;
+
+        let territory = board.territory();
+
+        assert_eq!(territory.size("a"), 1);
+        assert_eq!(territory.size("b"), 2);
+        assert_eq!(territory.size("c"), 0);
+    }
+}
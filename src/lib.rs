@@ -0,0 +1,12 @@
+#![cfg_attr(test, allow(clippy::bool_assert_comparison))]
+
+pub mod arena;
+pub mod config;
+pub mod game;
+pub mod metrics;
+pub mod record;
+pub mod replay;
+pub mod search;
+pub mod session;
+pub mod strategy;
+pub mod viewer;
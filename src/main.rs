@@ -1,33 +1,367 @@
-use actix_web::{get, middleware, post, web, App, HttpServer};
-use clap::Parser;
-use serde_json::{json, Value};
-mod game;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-const VERSION: &str = env!("CARGO_PKG_VERSION");
+use actix_web::{error, get, middleware, post, rt, web, App, HttpResponse, HttpServer, Scope};
+use battlesnake_rs::arena::{self, Arena, Ratings, Tournament};
+use battlesnake_rs::game;
+use battlesnake_rs::metrics::Metrics;
+use battlesnake_rs::record::{self, Entry, Recorder};
+use battlesnake_rs::replay::{self, Golden};
+use battlesnake_rs::search::Deadline;
+use battlesnake_rs::session::{self, Key, Session, Sessions};
+use battlesnake_rs::strategy::{
+    self, Appearance, Customization, Decision, Explanation, Greedy, Info, Pipeline, Strategy,
+    StrategyKind, Weights,
+};
+use battlesnake_rs::viewer::{self, Frame};
+use clap::{ArgEnum, Args, Parser, Subcommand};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::Deserialize;
+use serde_json::{json, Value};
 
 #[derive(Parser, Debug, Clone)]
-#[clap(version)]
+// An explicit `about`, as otherwise the doc comment of the last flattened flag group is used.
+#[clap(
+    version,
+    about = "A Battlesnake server, with an arena to play its strategies off against each other"
+)]
 struct Config {
     #[clap(short, long, default_value_t = 8080)]
     port: u16,
 
     #[clap(long, default_value_t = 1.5)]
     hunger_coefficient: f32,
+
+    /// Strategy served from the root routes. Every strategy is also served under its own prefix,
+    /// e.g. `/mcts/move`.
+    #[clap(long, arg_enum, default_value = "greedy")]
+    strategy: StrategyKind,
+
+    /// TOML or JSON file listing the greedy filters to run, in order.
+    #[clap(long)]
+    pipeline: Option<PathBuf>,
+
+    /// Comma separated greedy filters to run, in order. Takes precedence over `--pipeline`.
+    #[clap(long)]
+    filters: Option<Pipeline>,
+
+    #[clap(flatten)]
+    weights: Weights,
+
+    #[clap(flatten)]
+    customization: Customization,
+
+    /// TOML or JSON file customizing how the snake looks, for every route or per strategy under
+    /// `routes.<strategy>`. Flags and environment variables take precedence over the file's top
+    /// level, but not over its per-strategy settings.
+    #[clap(long, env = "BATTLESNAKE_APPEARANCE")]
+    appearance: Option<PathBuf>,
+
+    /// Directory to record every game played into, one JSONL file per game and snake.
+    #[clap(long)]
+    record: Option<PathBuf>,
+
+    /// Seconds a game can go without a request before its session is dropped, for games whose
+    /// `/end` never arrives.
+    #[clap(long, default_value_t = 900)]
+    session_ttl: u64,
+
+    /// Serve `POST /explain`, which takes a move request and answers with everything behind the
+    /// move. Meant for debugging, as it decides afresh on every call.
+    #[clap(long)]
+    explain: bool,
+
+    /// Serve a page at `/viewer` to watch games in progress, recently finished or recorded, move
+    /// by move.
+    #[clap(long)]
+    viewer: bool,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum Command {
+    /// Play games locally between our strategies instead of serving them.
+    Arena(ArenaConfig),
+    /// Rate strategy variants against each other over many local games.
+    Tournament(TournamentConfig),
+    /// Re-decide every turn of a recorded game and compare against what was played.
+    Replay(ReplayConfig),
+}
+
+#[derive(Args, Debug, Clone)]
+struct ArenaConfig {
+    /// Comma separated strategies to pit against each other, one snake each.
+    #[clap(
+        long,
+        arg_enum,
+        use_value_delimiter = true,
+        default_value = "greedy,scored"
+    )]
+    snakes: Vec<StrategyKind>,
+
+    #[clap(long, default_value = "standard")]
+    ruleset: String,
+
+    #[clap(long, default_value_t = 11)]
+    width: i16,
+
+    #[clap(long, default_value_t = 11)]
+    height: i16,
+
+    /// Move timeout in milliseconds, which the search strategies budget against.
+    #[clap(long, default_value_t = 500)]
+    timeout: u64,
+
+    #[clap(long, default_value_t = 1000)]
+    max_turns: u16,
+
+    #[clap(short, long, default_value_t = 1)]
+    games: usize,
+
+    /// Seed for starting positions and spawns, to replay a run exactly.
+    #[clap(long)]
+    seed: Option<u64>,
+}
+
+impl Config {
+    fn greedy(&self) -> anyhow::Result<Greedy> {
+        let pipeline = match (&self.filters, &self.pipeline) {
+            (Some(filters), _) => filters.clone(),
+            (None, Some(path)) => Pipeline::load(path)?,
+            (None, None) => Pipeline::default(),
+        };
+
+        Ok(Greedy::new(
+            self.hunger_coefficient,
+            pipeline,
+            self.weights.clone(),
+        ))
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+struct TournamentConfig {
+    /// TOML or JSON file listing the variants and setups to play. Defaults to greedy at a few
+    /// hunger coefficients, across standard, royale and duel games.
+    #[clap(long)]
+    config: Option<PathBuf>,
+
+    /// Games to play of each setup.
+    #[clap(short, long, default_value_t = 10)]
+    games: usize,
+
+    /// JSON file the ratings are read from and saved back to, so they build up across runs.
+    #[clap(long, default_value = "ratings.json")]
+    ratings: PathBuf,
+
+    /// Where to write the leaderboard, as JSON if it ends in `.json` and CSV otherwise.
+    #[clap(long, default_value = "leaderboard.csv")]
+    leaderboard: PathBuf,
+
+    #[clap(long)]
+    seed: Option<u64>,
+}
+
+#[derive(Args, Debug, Clone)]
+struct ReplayConfig {
+    /// A game recorded with `--record`.
+    file: PathBuf,
+
+    /// TOML or JSON file of moves expected on particular turns. Any miss exits with an error.
+    #[clap(long)]
+    golden: Option<PathBuf>,
+
+    /// Strategy to replay with, defaulting to the one that played the game.
+    #[clap(long, arg_enum)]
+    strategy: Option<StrategyKind>,
 }
 
-#[actix_web::main]
-async fn main() -> std::io::Result<()> {
+fn main() -> std::io::Result<()> {
     env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
 
     let config = Config::parse();
+    let greedy = config
+        .greedy()
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err))?;
+
+    match &config.command {
+        Some(Command::Arena(arena)) => {
+            play(arena, greedy);
+            Ok(())
+        }
+        Some(Command::Tournament(tournament)) => compete(tournament).map_err(std::io::Error::other),
+        Some(Command::Replay(replay)) => rerun(replay, greedy).map_err(std::io::Error::other),
+        None => actix_web::rt::System::new().block_on(serve(config, greedy)),
+    }
+}
+
+fn play(config: &ArenaConfig, greedy: Greedy) {
+    let arena = Arena {
+        ruleset: game::Ruleset {
+            name: config.ruleset.clone(),
+            ..Default::default()
+        },
+        width: config.width,
+        height: config.height,
+        timeout: config.timeout,
+        max_turns: config.max_turns,
+        snakes: config
+            .snakes
+            .iter()
+            .map(|kind| kind.build(greedy.clone()))
+            .collect(),
+    };
+
+    let mut rng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    for number in 0..config.games {
+        let summary = arena.play(&format!("arena-{}", number), &mut rng);
+        println!("{}", summary);
+    }
+}
+
+fn compete(config: &TournamentConfig) -> anyhow::Result<()> {
+    let tournament = match &config.config {
+        Some(path) => Tournament::load(path)?,
+        None => Tournament::default(),
+    };
+    let mut ratings = Ratings::load(&config.ratings)?;
+
+    let mut rng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    for summary in tournament.run(config.games, &mut ratings, &mut rng) {
+        println!("{}", summary);
+    }
+
+    ratings.save(&config.ratings)?;
+
+    let leaderboard = ratings.leaderboard();
+    arena::write_leaderboard(&leaderboard, &config.leaderboard)?;
+
+    for standing in leaderboard {
+        println!(
+            "{:>3}. {:<16} {:>7.1} ({} - {}) {} games, {} wins",
+            standing.rank,
+            standing.name,
+            standing.rating,
+            standing
+                .low
+                .map_or("?".to_string(), |low| format!("{:.1}", low)),
+            standing
+                .high
+                .map_or("?".to_string(), |high| format!("{:.1}", high)),
+            standing.games,
+            standing.wins,
+        );
+    }
+
+    Ok(())
+}
+
+fn rerun(config: &ReplayConfig, greedy: Greedy) -> anyhow::Result<()> {
+    let entries = record::read(&config.file)?;
+    let golden = match &config.golden {
+        Some(path) => Golden::load(path)?,
+        None => Golden::default(),
+    };
+
+    // Each snake replays with the strategy it played under, unless one is forced.
+    let strategy = |recorded: &str| {
+        config
+            .strategy
+            .or_else(|| StrategyKind::from_str(recorded, true).ok())
+            .unwrap_or(StrategyKind::Greedy)
+            .build(greedy.clone())
+    };
+
+    let report = replay::replay(&entries, &strategy, &golden)?;
+    println!("{}", report);
+
+    match report.failures().count() {
+        0 => Ok(()),
+        failures => Err(anyhow::anyhow!("{} golden moves missed", failures)),
+    }
+}
+
+async fn serve(config: Config, greedy: Greedy) -> std::io::Result<()> {
     let port = config.port;
 
-    println!("{:?}", config);
+    let strategies: Vec<Arc<dyn Strategy>> = StrategyKind::value_variants()
+        .iter()
+        .map(|kind| kind.build(greedy.clone()))
+        .collect();
+
+    let default = StrategyKind::value_variants()
+        .iter()
+        .position(|kind| kind == &config.strategy)
+        .map(|position| strategies[position].clone())
+        .expect("default strategy not found");
+
+    let appearance = match &config.appearance {
+        Some(path) => Appearance::load(path).map_err(std::io::Error::other)?,
+        None => Appearance::default(),
+    };
+    let info = |strategy: &Arc<dyn Strategy>| {
+        web::Data::new(appearance.info(strategy.name(), strategy.info(), &config.customization))
+    };
+    let infos: Vec<web::Data<Info>> = strategies.iter().map(info).collect();
+    let default_info = info(&default);
+
+    let recorder = match &config.record {
+        Some(dir) => Some(web::Data::new(
+            Recorder::new(dir).map_err(std::io::Error::other)?,
+        )),
+        None => None,
+    };
+
+    let explain = config.explain;
+    let viewer = config.viewer;
+    let metrics = web::Data::new(Metrics::default());
+    let sessions = web::Data::new(Sessions::new(Duration::from_secs(config.session_ttl)));
+    rt::spawn(sweep(sessions.clone()));
+
+    log::info!(target: "server", "config={:?}", config);
     HttpServer::new(move || {
-        App::new()
-            .app_data(web::Data::new(config.clone()))
-            .wrap(middleware::Logger::default())
-            .service(index)
+        let mut app = App::new()
+            .app_data(web::Data::from(default.clone()))
+            .app_data(default_info.clone())
+            .app_data(sessions.clone())
+            .app_data(metrics.clone())
+            .wrap(middleware::Logger::default());
+
+        if let Some(recorder) = &recorder {
+            app = app.app_data(recorder.clone());
+        }
+
+        for (strategy, info) in strategies.iter().zip(infos.iter()) {
+            app = app.service(strategy_scope(strategy.clone(), info.clone(), explain));
+        }
+
+        if explain {
+            app = app.service(explanation);
+        }
+
+        if viewer {
+            app = app
+                .service(viewer_page)
+                .service(viewer_games)
+                .service(viewer_live)
+                .service(viewer_recorded);
+        }
+
+        app.service(metrics_endpoint)
+            .route("/", web::get().to(index))
             .service(start)
             .service(mv)
             .service(end)
@@ -37,43 +371,518 @@ async fn main() -> std::io::Result<()> {
     .await
 }
 
-#[get("/")]
-async fn index() -> web::Json<Value> {
+/// Drops expired sessions every so often, for games whose `/end` never arrives.
+async fn sweep(sessions: web::Data<Sessions>) {
+    let mut interval = rt::time::interval(sessions.sweep_interval());
+    loop {
+        interval.tick().await;
+
+        let expired = sessions.sweep();
+        if expired > 0 {
+            log::info!(target: "session", "dropped {} expired sessions", expired);
+        }
+    }
+}
+
+/// Every route for one strategy, under its name.
+fn strategy_scope(strategy: Arc<dyn Strategy>, info: web::Data<Info>, explain: bool) -> Scope {
+    let mut scope = web::scope(&format!("/{}", strategy.name()))
+        .app_data(web::Data::from(strategy))
+        .app_data(info)
+        // Engines are usually given the bare prefix, but take it with a trailing slash too.
+        .route("", web::get().to(index))
+        .route("/", web::get().to(index))
+        .service(start)
+        .service(mv)
+        .service(end);
+
+    if explain {
+        scope = scope.service(explanation);
+    }
+
+    scope
+}
+
+async fn index(info: web::Data<Info>) -> web::Json<Value> {
     web::Json(json!({
         "apiversion": "1",
-        "author": "broothie",
-        "color": "#DB5527",
-        "head": "tongue",
-        "tail": "block-bum",
-        "version": VERSION,
+        "author": info.author,
+        "color": info.color,
+        "head": info.head,
+        "tail": info.tail,
+        "version": info.version,
     }))
 }
 
 #[post("/start")]
-async fn start() -> String {
-    "start".to_string()
+async fn start(
+    strategy: web::Data<dyn Strategy>,
+    sessions: web::Data<Sessions>,
+    recorder: Option<web::Data<Recorder>>,
+    request: web::Json<Value>,
+) -> actix_web::Result<String> {
+    let state = parse(&request)?;
+
+    {
+        let state = state.clone();
+        web::block(move || {
+            let session = sessions.start(&state);
+            strategy.start(&state, &mut session::lock(&session));
+        })
+        .await?;
+    }
+
+    record(
+        recorder,
+        &state,
+        Entry::Start {
+            request: request.into_inner(),
+        },
+    )
+    .await;
+
+    Ok("start".to_string())
 }
 
 #[post("/move")]
-async fn mv(data: web::Data<Config>, state: web::Json<game::State>) -> web::Json<Value> {
-    println!(
-        "game {}, turn {}: {:?}",
-        state.game.id, state.turn, state.game
+async fn mv(
+    strategy: web::Data<dyn Strategy>,
+    sessions: web::Data<Sessions>,
+    metrics: web::Data<Metrics>,
+    recorder: Option<web::Data<Recorder>>,
+    request: web::Json<Value>,
+) -> actix_web::Result<web::Json<Value>> {
+    let state = parse(&request)?;
+
+    log::debug!(
+        target: "move",
+        "game={} turn={} strategy={} ruleset={:?} timeout={}",
+        state.game.id,
+        state.turn,
+        strategy.name(),
+        state.game.ruleset,
+        state.game.timeout
     );
 
-    let (mv, shout) = state
-        .decide(data.hunger_coefficient)
-        .unwrap_or_else(|_| (game::Move::Up, "".to_string()));
+    let received = Instant::now();
+
+    // Worked out before deciding, so there's something sensible to play whatever happens.
+    let fallback = strategy::safe_move(&state);
+    let deadline = Deadline::response(&state.game);
+    // Counted from now rather than once the session is free, so a strategy kept waiting by the
+    // last move running over doesn't run over in turn.
+    let budget = Deadline::for_game(&state.game);
+
+    let session = sessions.get(&state);
+    let decided = {
+        let strategy = strategy.clone();
+        let state = state.clone();
+
+        // On its own thread, so a slow strategy can be cut off, and a panic doesn't take the
+        // request with it.
+        web::block(move || {
+            let mut session = session::lock(&session);
+            if budget.expired() {
+                let waited = anyhow::anyhow!("the session was busy until past the deadline");
+                return Ok(Err(waited));
+            }
 
-    println!(
-        "game {}, turn {}: {:?} '{}'",
-        state.game.id, state.turn, mv, shout
+            let started = Instant::now();
+            let decision = panic::catch_unwind(AssertUnwindSafe(|| {
+                strategy.decide(&state, &mut session, budget)
+            }));
+            session.timed(started.elapsed());
+
+            decision
+        })
+    };
+
+    let decision = match rt::time::timeout(deadline.remaining(), decided).await {
+        Ok(Ok(Ok(Ok(decision)))) => Ok(decision),
+        Ok(Ok(Ok(Err(err)))) => Err(Incident::Failed(err)),
+        Ok(Ok(Err(payload))) => Err(Incident::Panicked(panic_message(payload))),
+        Ok(Err(err)) => Err(Incident::Panicked(err.to_string())),
+        Err(_) => Err(Incident::TimedOut(received.elapsed())),
+    };
+
+    if let Ok(Decision {
+        trace: Some(trace), ..
+    }) = &decision
+    {
+        trace.log(&state);
+        metrics.traced(trace);
+    }
+
+    let Decision { mv, shout, trace } = decision.unwrap_or_else(|incident| {
+        log::log!(
+            target: "incident",
+            incident.level(),
+            "game={} turn={} strategy={} reason={} fallback={:?}: {}",
+            state.game.id,
+            state.turn,
+            strategy.name(),
+            incident.reason(),
+            fallback,
+            incident
+        );
+        metrics.fell_back(strategy.name(), incident.reason());
+
+        Decision::new(fallback)
+    });
+    metrics.moved(strategy.name(), received.elapsed());
+
+    log::info!(
+        target: "move",
+        "game={} turn={} strategy={} move={:?} shout={:?} elapsed_us={}",
+        state.game.id,
+        state.turn,
+        strategy.name(),
+        mv,
+        shout,
+        received.elapsed().as_micros()
     );
 
-    web::Json(json!({ "move": mv, "shout": shout }))
+    let response = json!({ "move": mv, "shout": shout });
+    let played = Entry::Move {
+        request: request.into_inner(),
+        strategy: strategy.name().to_string(),
+        mv,
+        shout,
+        trace,
+    };
+    sessions.played(&state, played.clone());
+    record(recorder, &state, played).await;
+
+    Ok(web::Json(response))
 }
 
 #[post("/end")]
-async fn end() -> String {
-    "end".to_string()
+async fn end(
+    strategy: web::Data<dyn Strategy>,
+    sessions: web::Data<Sessions>,
+    metrics: web::Data<Metrics>,
+    recorder: Option<web::Data<Recorder>>,
+    request: web::Json<Value>,
+) -> actix_web::Result<String> {
+    let state = parse(&request)?;
+    metrics.ended(&state);
+
+    {
+        let state = state.clone();
+        web::block(move || {
+            let session = sessions
+                .end(&state)
+                .unwrap_or_else(|| Arc::new(Mutex::new(Session::new(&state))));
+            let mut session = session::lock(&session);
+            strategy.end(&state, &mut session);
+
+            log::info!(
+                target: "session",
+                "game={} turn={} strategy={} moves={} elapsed={:?} average={:?} slowest={:?}",
+                state.game.id,
+                state.turn,
+                strategy.name(),
+                session.moves,
+                session.started.elapsed(),
+                session.average_decide_time().unwrap_or_default(),
+                session.slowest
+            );
+        })
+        .await?;
+    }
+
+    record(
+        recorder,
+        &state,
+        Entry::End {
+            request: request.into_inner(),
+        },
+    )
+    .await;
+
+    Ok("end".to_string())
+}
+
+#[get("/viewer")]
+async fn viewer_page() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(viewer::PAGE)
+}
+
+#[get("/viewer/games")]
+async fn viewer_games(
+    sessions: web::Data<Sessions>,
+    recorder: Option<web::Data<Recorder>>,
+) -> actix_web::Result<web::Json<Value>> {
+    let games = |keys: Vec<Key>| -> Vec<Value> {
+        keys.into_iter()
+            .map(|(game, snake)| json!({ "game": game, "snake": snake }))
+            .collect()
+    };
+
+    let recorded = match &recorder {
+        Some(recorder) => recorder.games().map_err(error::ErrorInternalServerError)?,
+        None => vec![],
+    };
+
+    Ok(web::Json(json!({
+        "live": games(sessions.keys()),
+        "finished": games(sessions.finished()),
+        "recorded": recorded,
+        "recording": recorder.is_some(),
+    })))
+}
+
+#[get("/viewer/live/{game}/{snake}")]
+async fn viewer_live(
+    sessions: web::Data<Sessions>,
+    path: web::Path<(String, String)>,
+) -> actix_web::Result<web::Json<Vec<Frame>>> {
+    let (game, snake) = path.into_inner();
+    let history = sessions
+        .history(&game, &snake)
+        .ok_or_else(|| error::ErrorNotFound(format!("no session for {} in {}", snake, game)))?;
+
+    let frames = web::block(move || viewer::frames(&history))
+        .await?
+        .map_err(error::ErrorInternalServerError)?;
+    Ok(web::Json(frames))
+}
+
+#[get("/viewer/recorded/{game}/{snake}")]
+async fn viewer_recorded(
+    recorder: Option<web::Data<Recorder>>,
+    path: web::Path<(String, String)>,
+) -> actix_web::Result<web::Json<Vec<Frame>>> {
+    let (game, snake) = path.into_inner();
+    let recorder = recorder.ok_or_else(|| error::ErrorNotFound("not recording games"))?;
+    let path = recorder.path(&game, &snake);
+    if !path.exists() {
+        return Err(error::ErrorNotFound(format!(
+            "no recording of {} in {}",
+            snake, game
+        )));
+    }
+
+    let frames =
+        web::block(move || record::read(&path).and_then(|entries| viewer::frames(&entries)))
+            .await?
+            .map_err(error::ErrorInternalServerError)?;
+    Ok(web::Json(frames))
+}
+
+#[get("/metrics")]
+async fn metrics_endpoint(
+    metrics: web::Data<Metrics>,
+    sessions: web::Data<Sessions>,
+) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(metrics.render(sessions.len()))
+}
+
+#[post("/explain")]
+async fn explanation(
+    strategy: web::Data<dyn Strategy>,
+    request: web::Json<Value>,
+) -> actix_web::Result<web::Json<Explanation>> {
+    let state = parse(&request)?;
+
+    let explanation = web::block(move || Explanation::new(strategy.as_ref(), &state))
+        .await?
+        .map_err(error::ErrorInternalServerError)?;
+
+    Ok(web::Json(explanation))
+}
+
+/// Something that went wrong deciding a move, so the fallback was played instead.
+#[derive(Debug)]
+enum Incident {
+    Failed(anyhow::Error),
+    Panicked(String),
+    /// Cut off after this long.
+    TimedOut(Duration),
+}
+
+impl Incident {
+    /// How the incident is labelled in metrics.
+    fn reason(&self) -> &'static str {
+        match self {
+            Incident::Failed(_) => "failed",
+            Incident::Panicked(_) => "panicked",
+            Incident::TimedOut(_) => "timed_out",
+        }
+    }
+
+    /// A panic is a bug, where the others may just be a slow or unlucky turn.
+    fn level(&self) -> log::Level {
+        match self {
+            Incident::Panicked(_) => log::Level::Error,
+            Incident::Failed(_) | Incident::TimedOut(_) => log::Level::Warn,
+        }
+    }
+}
+
+impl fmt::Display for Incident {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Incident::Failed(err) => write!(f, "failed: {:#}", err),
+            Incident::Panicked(message) => write!(f, "panicked: {}", message),
+            Incident::TimedOut(elapsed) => write!(f, "timed out after {:?}", elapsed),
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Requests are taken as plain JSON so they can be recorded exactly as they were sent.
+fn parse(request: &Value) -> actix_web::Result<game::State> {
+    game::State::deserialize(request).map_err(error::ErrorBadRequest)
+}
+
+/// Writes on a blocking thread, so a slow disk never holds up a worker.
+async fn record(recorder: Option<web::Data<Recorder>>, state: &game::State, entry: Entry) {
+    let recorder = match recorder {
+        Some(recorder) => recorder,
+        None => return,
+    };
+
+    let (game, snake) = (state.game.id.clone(), state.you.id.clone());
+    let recorded = web::block(move || recorder.record(&game, &snake, &entry)).await;
+
+    if let Err(err) = recorded
+        .map_err(anyhow::Error::from)
+        .and_then(|recorded| recorded)
+    {
+        log::error!(
+            target: "record",
+            "game={} turn={} snake={} recording failed: {:#}",
+            state.game.id,
+            state.turn,
+            state.you.id,
+            err
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use actix_web::test;
+
+    use super::*;
+
+    /// Ignores its deadline and takes far too long about it.
+    #[derive(Default)]
+    struct Slow {
+        calls: AtomicUsize,
+    }
+
+    impl Strategy for Slow {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        fn decide(
+            &self,
+            _state: &game::State,
+            _session: &mut Session,
+            _deadline: Deadline,
+        ) -> anyhow::Result<Decision> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(300));
+            Ok(Decision::new(game::Move::Up))
+        }
+    }
+
+    #[actix_web::test]
+    async fn version_flags() {
+        for flag in ["--version", "-V"] {
+            let err = Config::try_parse_from(["battlesnake-rs", flag]).expect_err("prints version");
+            assert_eq!(err.kind(), clap::ErrorKind::DisplayVersion, "{}", flag);
+        }
+
+        let config = Config::try_parse_from(["battlesnake-rs", "--snake-version", "1.2"])
+            .expect("must parse");
+        assert_eq!(config.customization.version.as_deref(), Some("1.2"));
+    }
+
+    #[actix_web::test]
+    async fn index_under_prefix() {
+        let greedy: Arc<dyn Strategy> =
+            Arc::new(Greedy::new(1.5, Pipeline::default(), Weights::default()));
+        let app = test::init_service(App::new().service(strategy_scope(
+            greedy,
+            web::Data::new(Info::default()),
+            false,
+        )))
+        .await;
+
+        for uri in ["/greedy", "/greedy/"] {
+            let response =
+                test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+            assert!(response.status().is_success(), "{}", uri);
+
+            let body: Value = test::read_body_json(response).await;
+            assert_eq!(body["apiversion"], "1");
+        }
+    }
+
+    #[actix_web::test]
+    async fn overrun_skips_the_next_decision() {
+        let slow = Arc::new(Slow::default());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(Sessions::new(Duration::from_secs(60))))
+                .app_data(web::Data::new(Metrics::default()))
+                .service(strategy_scope(
+                    slow.clone(),
+                    web::Data::new(Info::default()),
+                    false,
+                )),
+        )
+        .await;
+
+        let mut state: game::State = "
+            turn: 1
+            ruleset: solo
+
+            4  . . . . .
+            3  . . . . .
+            2  a . . . .
+            1  a . . . .
+            0  A . . . .
+
+            A: id=you health=90
+        "
+        .parse()
+        .expect("must parse");
+        state.game.timeout = 150;
+        let request = serde_json::to_value(&state).expect("must serialize");
+
+        for _ in 0..2 {
+            let response = test::call_service(
+                &app,
+                test::TestRequest::post()
+                    .uri("/slow/move")
+                    .set_json(&request)
+                    .to_request(),
+            )
+            .await;
+            let body: Value = test::read_body_json(response).await;
+            assert_eq!(body["move"], "right");
+        }
+
+        // The second move waited on the first for the session, and gave up rather than run over.
+        rt::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(slow.calls.load(Ordering::SeqCst), 1);
+    }
 }
@@ -0,0 +1,313 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::game::State;
+use crate::strategy::{DecisionTrace, Step};
+
+/// Upper bounds of the move latency buckets, in seconds, up to the usual 500ms timeout and past.
+const LATENCY_BUCKETS: [f64; 10] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.2, 0.3, 0.4, 0.5, 1.0];
+
+/// How a game ended for us.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Finish {
+    Win,
+    Loss,
+    Draw,
+}
+
+impl Finish {
+    /// Judged from the final state sent to `/end`.
+    pub fn of(state: &State) -> Finish {
+        let alive = state
+            .board
+            .snakes
+            .iter()
+            .any(|snake| snake.id == state.you.id);
+
+        match (alive, state.board.snakes.is_empty()) {
+            (true, _) => Finish::Win,
+            (false, true) => Finish::Draw,
+            (false, false) => Finish::Loss,
+        }
+    }
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Finish::Win => "win",
+            Finish::Loss => "loss",
+            Finish::Draw => "draw",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    /// Observations at or under each of `LATENCY_BUCKETS`, not cumulative.
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        if let Some(bucket) = LATENCY_BUCKETS.iter().position(|bound| seconds <= *bound) {
+            self.buckets[bucket] += 1;
+        }
+
+        self.count += 1;
+        self.sum += seconds;
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counts {
+    latency: BTreeMap<String, Histogram>,
+    steps: BTreeMap<(String, &'static str), u64>,
+    fallbacks: BTreeMap<(String, String), u64>,
+    games: BTreeMap<(String, Finish), u64>,
+}
+
+/// Counts of what the server has been up to, rendered in the Prometheus text format.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    counts: Mutex<Counts>,
+}
+
+impl Metrics {
+    /// Time taken to answer a move, fallbacks included.
+    pub fn moved(&self, strategy: &str, elapsed: Duration) {
+        self.counts()
+            .latency
+            .entry(strategy.to_string())
+            .or_default()
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub fn traced(&self, trace: &DecisionTrace) {
+        let mut counts = self.counts();
+        for step in trace.steps.iter() {
+            *counts
+                .steps
+                .entry((step.filter.key().to_string(), step_outcome(step)))
+                .or_default() += 1;
+        }
+    }
+
+    /// A move the strategy didn't give, and why.
+    pub fn fell_back(&self, strategy: &str, reason: &str) {
+        *self
+            .counts()
+            .fallbacks
+            .entry((strategy.to_string(), reason.to_string()))
+            .or_default() += 1;
+    }
+
+    pub fn ended(&self, state: &State) {
+        *self
+            .counts()
+            .games
+            .entry((state.game.ruleset.name.clone(), Finish::of(state)))
+            .or_default() += 1;
+    }
+
+    pub fn render(&self, active_sessions: usize) -> String {
+        let counts = self.counts();
+        let mut out = String::new();
+
+        header(
+            &mut out,
+            "battlesnake_move_duration_seconds",
+            "histogram",
+            "Time taken to answer a move.",
+        );
+        for (strategy, histogram) in counts.latency.iter() {
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets.iter()) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "battlesnake_move_duration_seconds_bucket{{strategy=\"{}\",le=\"{}\"}} {}",
+                    escape(strategy),
+                    bound,
+                    cumulative
+                );
+            }
+            let _ = writeln!(
+                out,
+                "battlesnake_move_duration_seconds_bucket{{strategy=\"{}\",le=\"+Inf\"}} {}",
+                escape(strategy),
+                histogram.count
+            );
+            let _ = writeln!(
+                out,
+                "battlesnake_move_duration_seconds_sum{{strategy=\"{}\"}} {}",
+                escape(strategy),
+                histogram.sum
+            );
+            let _ = writeln!(
+                out,
+                "battlesnake_move_duration_seconds_count{{strategy=\"{}\"}} {}",
+                escape(strategy),
+                histogram.count
+            );
+        }
+
+        header(
+            &mut out,
+            "battlesnake_filter_steps_total",
+            "counter",
+            "Greedy filter steps, by whether they narrowed the moves, changed nothing, or were skipped.",
+        );
+        for ((filter, outcome), count) in counts.steps.iter() {
+            let _ = writeln!(
+                out,
+                "battlesnake_filter_steps_total{{filter=\"{}\",outcome=\"{}\"}} {}",
+                filter, outcome, count
+            );
+        }
+
+        header(
+            &mut out,
+            "battlesnake_fallbacks_total",
+            "counter",
+            "Moves answered with the safe fallback, by why the strategy didn't give one.",
+        );
+        for ((strategy, reason), count) in counts.fallbacks.iter() {
+            let _ = writeln!(
+                out,
+                "battlesnake_fallbacks_total{{strategy=\"{}\",reason=\"{}\"}} {}",
+                escape(strategy),
+                escape(reason),
+                count
+            );
+        }
+
+        header(
+            &mut out,
+            "battlesnake_active_sessions",
+            "gauge",
+            "Games in progress.",
+        );
+        let _ = writeln!(out, "battlesnake_active_sessions {}", active_sessions);
+
+        header(
+            &mut out,
+            "battlesnake_games_total",
+            "counter",
+            "Games finished, by ruleset and how they went for us.",
+        );
+        for ((ruleset, finish), count) in counts.games.iter() {
+            let _ = writeln!(
+                out,
+                "battlesnake_games_total{{ruleset=\"{}\",outcome=\"{}\"}} {}",
+                escape(ruleset),
+                finish.key(),
+                count
+            );
+        }
+
+        out
+    }
+
+    fn counts(&self) -> MutexGuard<'_, Counts> {
+        self.counts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn step_outcome(step: &Step) -> &'static str {
+    if step.skipped {
+        "skipped"
+    } else if step.before == step.after {
+        "unchanged"
+    } else {
+        "narrowed"
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::strategy::{Greedy, Pipeline, Weights};
+
+    use super::*;
+
+    #[test]
+    fn finish() {
+        let mut state: State = "A a . B b".parse().expect("must parse");
+        assert_eq!(Finish::of(&state), Finish::Win);
+
+        state.board.snakes.remove(0);
+        assert_eq!(Finish::of(&state), Finish::Loss);
+
+        state.board.snakes.clear();
+        assert_eq!(Finish::of(&state), Finish::Draw);
+    }
+
+    #[test]
+    fn render() {
+        let state: State = "
+            ruleset: royale
+            . . . .
+            A a a .
+        "
+        .parse()
+        .expect("must parse");
+        let metrics = Metrics::default();
+
+        metrics.moved("greedy", Duration::from_millis(30));
+        metrics.moved("greedy", Duration::from_millis(700));
+        metrics.moved("mc\"ts", Duration::from_secs(2));
+        metrics.traced(
+            &Greedy::new(1.5, Pipeline::default(), Weights::default()).decide_traced(&state),
+        );
+        metrics.fell_back("greedy", "panicked");
+        metrics.ended(&state);
+
+        let rendered = metrics.render(3);
+        let has = |line: &str| rendered.lines().any(|l| l == line);
+
+        assert!(has(
+            r#"battlesnake_move_duration_seconds_bucket{strategy="greedy",le="0.025"} 0"#
+        ));
+        assert!(has(
+            r#"battlesnake_move_duration_seconds_bucket{strategy="greedy",le="0.05"} 1"#
+        ));
+        assert!(has(
+            r#"battlesnake_move_duration_seconds_bucket{strategy="greedy",le="1"} 2"#
+        ));
+        assert!(has(
+            r#"battlesnake_move_duration_seconds_bucket{strategy="mc\"ts",le="1"} 0"#
+        ));
+        assert!(has(
+            r#"battlesnake_move_duration_seconds_bucket{strategy="mc\"ts",le="+Inf"} 1"#
+        ));
+        assert!(has(
+            r#"battlesnake_move_duration_seconds_count{strategy="greedy"} 2"#
+        ));
+        assert!(has(
+            r#"battlesnake_filter_steps_total{filter="in_bounds",outcome="narrowed"} 1"#
+        ));
+        assert!(has(
+            r#"battlesnake_fallbacks_total{strategy="greedy",reason="panicked"} 1"#
+        ));
+        assert!(has("battlesnake_active_sessions 3"));
+        assert!(has(
+            r#"battlesnake_games_total{ruleset="royale",outcome="win"} 1"#
+        ));
+    }
+}
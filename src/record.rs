@@ -0,0 +1,215 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::game::Move;
+use crate::strategy::DecisionTrace;
+
+/// One line of a recorded game: a request from the engine, as it was sent, and what we answered.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Entry {
+    Start {
+        request: Value,
+    },
+    Move {
+        request: Value,
+        strategy: String,
+        #[serde(rename = "move")]
+        mv: Move,
+        shout: String,
+        /// Why the strategy chose its move, if it keeps track.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trace: Option<DecisionTrace>,
+    },
+    End {
+        request: Value,
+    },
+}
+
+impl Entry {
+    pub fn request(&self) -> &Value {
+        match self {
+            Entry::Start { request } | Entry::Move { request, .. } | Entry::End { request } => {
+                request
+            }
+        }
+    }
+}
+
+/// A recorded game, named as in its file name.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Recording {
+    pub game: String,
+    pub snake: String,
+}
+
+/// Appends every request a snake gets in a game to `<dir>/<game id>.<snake id>.jsonl`, one entry
+/// per line, so two of our snakes in the same game each get their own file.
+#[derive(Debug)]
+pub struct Recorder {
+    dir: PathBuf,
+}
+
+impl Recorder {
+    pub fn new(dir: &Path) -> Result<Recorder> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create recording directory {}", dir.display()))?;
+
+        Ok(Recorder {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    /// Where a snake's game is recorded. Anything in the ids that isn't safe in a file name is
+    /// replaced, dots included, so the one dot left splits the two apart.
+    pub fn path(&self, game_id: &str, snake_id: &str) -> PathBuf {
+        self.dir.join(format!(
+            "{}.{}.jsonl",
+            file_name(game_id),
+            file_name(snake_id)
+        ))
+    }
+
+    /// Every recorded game, most recently played first.
+    pub fn games(&self) -> Result<Vec<Recording>> {
+        let mut games: Vec<(SystemTime, Recording)> = Vec::new();
+
+        for file in fs::read_dir(&self.dir)
+            .with_context(|| format!("failed to list recordings in {}", self.dir.display()))?
+        {
+            let path = file?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("jsonl") {
+                continue;
+            }
+
+            let name = path.file_stem().and_then(|stem| stem.to_str());
+            if let Some((game, snake)) = name.and_then(|name| name.split_once('.')) {
+                let modified = fs::metadata(&path)?.modified()?;
+                games.push((
+                    modified,
+                    Recording {
+                        game: game.to_string(),
+                        snake: snake.to_string(),
+                    },
+                ));
+            }
+        }
+
+        games.sort_by(|a, b| b.cmp(a));
+        Ok(games.into_iter().map(|(_, recording)| recording).collect())
+    }
+
+    /// Blocks on the file system, so keep it off the server's workers. Each entry goes out in a
+    /// single append, which keeps lines whole without any locking.
+    pub fn record(&self, game_id: &str, snake_id: &str, entry: &Entry) -> Result<()> {
+        let path = self.path(game_id, snake_id);
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .with_context(|| format!("failed to record to {}", path.display()))
+    }
+}
+
+fn file_name(id: &str) -> String {
+    id.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Reads back every entry of a recorded game.
+pub fn read(path: &Path) -> Result<Vec<Entry>> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("failed to read recording {}", path.display()))?;
+
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("bad entry on line {} of {}", number + 1, path.display()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn record_and_read() {
+        let dir = std::env::temp_dir().join(format!("battlesnake-record-{}", std::process::id()));
+        let recorder = Recorder::new(&dir).expect("must create");
+
+        let entries = vec![
+            Entry::Start {
+                request: json!({"turn": 0}),
+            },
+            Entry::Move {
+                request: json!({"turn": 0}),
+                strategy: "greedy".to_string(),
+                mv: Move::Left,
+                shout: "gulp".to_string(),
+                trace: None,
+            },
+            Entry::End {
+                request: json!({"turn": 1}),
+            },
+        ];
+
+        for entry in entries.iter() {
+            recorder.record("a/b c", "you", entry).expect("must record");
+        }
+        recorder
+            .record("a/b c", "x.y", &entries[0])
+            .expect("must record");
+
+        let path = recorder.path("a/b c", "you");
+        assert_eq!(path, dir.join("a_b_c.you.jsonl"));
+
+        let mut games = recorder.games().expect("must list");
+        games.sort_by(|a, b| a.snake.cmp(&b.snake));
+        assert_eq!(
+            games,
+            vec![
+                Recording {
+                    game: "a_b_c".to_string(),
+                    snake: "x_y".to_string(),
+                },
+                Recording {
+                    game: "a_b_c".to_string(),
+                    snake: "you".to_string(),
+                },
+            ]
+        );
+
+        let contents = fs::read_to_string(&path).expect("must exist");
+        assert!(contents
+            .lines()
+            .nth(1)
+            .expect("must have a move")
+            .contains(r#""kind":"move""#));
+        assert_eq!(read(&path).expect("must read"), entries);
+
+        fs::remove_dir_all(&dir).expect("must clean up");
+    }
+}
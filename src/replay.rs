@@ -0,0 +1,455 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::config::load_config;
+use crate::game::{Move, State};
+use crate::record::Entry;
+use crate::search::Deadline;
+use crate::session::Session;
+use crate::strategy::Strategy;
+
+/// Moves known to be right on particular turns of a recorded game, so a blunder that's been fixed
+/// stays fixed.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Golden {
+    #[serde(default)]
+    pub expect: Vec<Expectation>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Expectation {
+    pub turn: u16,
+    /// Which of our snakes this is for, only needed when the recording has more than one.
+    #[serde(default)]
+    pub snake: Option<String>,
+    /// Any of these moves passes.
+    pub moves: Vec<Move>,
+    /// Why the other moves are wrong.
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+impl Golden {
+    pub fn load(path: &Path) -> Result<Golden> {
+        load_config(path, "golden moves")
+    }
+
+    pub fn get(&self, turn: u16, snake: &str) -> Option<&Expectation> {
+        self.expect
+            .iter()
+            .find(|expectation| expectation.matches(turn, snake))
+    }
+}
+
+impl Expectation {
+    fn matches(&self, turn: u16, snake: &str) -> bool {
+        self.turn == turn && self.snake.as_deref().is_none_or(|id| id == snake)
+    }
+}
+
+/// What the strategy does now on one recorded turn, next to what was played at the time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Turn {
+    pub turn: u16,
+    pub snake: String,
+    pub recorded: Move,
+    /// `None` if the strategy failed to decide.
+    pub chosen: Option<Move>,
+    pub expected: Option<Expectation>,
+}
+
+impl Turn {
+    pub fn changed(&self) -> bool {
+        self.chosen != Some(self.recorded)
+    }
+
+    pub fn failed(&self) -> bool {
+        match (&self.expected, self.chosen) {
+            (Some(expected), Some(chosen)) => !expected.moves.contains(&chosen),
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub game: String,
+    pub turns: Vec<Turn>,
+}
+
+impl Report {
+    pub fn changed(&self) -> impl Iterator<Item = &Turn> {
+        self.turns.iter().filter(|turn| turn.changed())
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &Turn> {
+        self.turns.iter().filter(|turn| turn.failed())
+    }
+}
+
+/// Asks a strategy for a move on every recorded turn and checks it against both the recorded and
+/// the golden moves. Each of our snakes in the recording is replayed with the strategy `strategy`
+/// gives for the name it was first recorded playing under, keeping one session across its turns
+/// as in the game: started on the snake's first move and ended on its `/end`. Fails if a golden move is given for a turn the recording doesn't have, or
+/// doesn't say which snake it's for when there's more than one.
+pub fn replay(
+    entries: &[Entry],
+    strategy: &dyn Fn(&str) -> Arc<dyn Strategy>,
+    golden: &Golden,
+) -> Result<Report> {
+    let mut game = String::new();
+    let mut turns: Vec<Turn> = Vec::new();
+    let mut players: HashMap<String, (Arc<dyn Strategy>, Session)> = HashMap::new();
+
+    for entry in entries {
+        let (request, name, recorded) = match entry {
+            Entry::Move {
+                request,
+                strategy,
+                mv,
+                ..
+            } => (request, strategy, *mv),
+            Entry::End { request } => {
+                let state = State::deserialize(request).context("bad recorded request")?;
+                if let Some((strategy, session)) = players.get_mut(&state.you.id) {
+                    strategy.end(&state, session);
+                }
+                continue;
+            }
+            Entry::Start { .. } => continue,
+        };
+
+        let state = State::deserialize(request).context("bad recorded request")?;
+        game = state.game.id.clone();
+        let (strategy, session) = players.entry(state.you.id.clone()).or_insert_with(|| {
+            let strategy = strategy(name);
+            let mut session = Session::new(&state);
+            strategy.start(&state, &mut session);
+            (strategy, session)
+        });
+
+        turns.push(Turn {
+            turn: state.turn,
+            snake: state.you.id.clone(),
+            recorded,
+            chosen: strategy
+                .decide(&state, session, Deadline::for_game(&state.game))
+                .ok()
+                .map(|decision| decision.mv),
+            expected: golden.get(state.turn, &state.you.id).cloned(),
+        });
+    }
+
+    if players.len() > 1 {
+        if let Some(vague) = golden.expect.iter().find(|e| e.snake.is_none()) {
+            return Err(anyhow!(
+                "golden move for turn {} doesn't say which of the {} snakes it's for",
+                vague.turn,
+                players.len()
+            ));
+        }
+    }
+
+    if let Some(missing) = golden.expect.iter().find(|expectation| {
+        turns
+            .iter()
+            .all(|turn| !expectation.matches(turn.turn, &turn.snake))
+    }) {
+        return Err(anyhow!(
+            "golden move for turn {}, which isn't in the recording",
+            missing.turn
+        ));
+    }
+
+    Ok(Report { game, turns })
+}
+
+impl fmt::Display for Turn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let chosen = match self.chosen {
+            Some(chosen) => format!("{:?}", chosen),
+            None => "error".to_string(),
+        };
+
+        let line = format!(
+            "turn {:>4} {}: recorded {:<5} chose {:<5}",
+            self.turn,
+            self.snake,
+            format!("{:?}", self.recorded),
+            chosen
+        );
+
+        let line = match &self.expected {
+            Some(expected) => format!(
+                "{} expected {:?}{}",
+                line,
+                expected.moves,
+                expected
+                    .note
+                    .as_ref()
+                    .map(|note| format!(" ({})", note))
+                    .unwrap_or_default()
+            ),
+            None => line,
+        };
+
+        if self.failed() {
+            write!(f, "{}", line.red())
+        } else if self.changed() {
+            write!(f, "{}", line.yellow())
+        } else {
+            write!(f, "{}", line)
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for turn in self.turns.iter() {
+            writeln!(f, "{}", turn)?;
+        }
+
+        write!(
+            f,
+            "game {}: {} turns, {} changed, {} failed",
+            self.game,
+            self.turns.len(),
+            self.changed().count(),
+            self.failures().count()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use serde_json::Value;
+
+    use crate::strategy::{Decision, Greedy, Weights};
+
+    use super::*;
+
+    /// A snake with the given id in the bottom left corner heading down, with nowhere to go but
+    /// right.
+    fn request(id: &str, turn: u16) -> Value {
+        let state: State = format!(
+            "
+            turn: {}
+            ruleset: solo
+
+            4  . . . . .
+            3  . . . . .
+            2  a . . . .
+            1  a . . . .
+            0  A . . . .
+
+            A: id={} health=90
+            ",
+            turn, id
+        )
+        .parse()
+        .expect("must parse");
+
+        serde_json::to_value(&state).expect("must serialize")
+    }
+
+    fn moved(turn: u16, mv: Move) -> Entry {
+        moved_as("you", turn, mv)
+    }
+
+    /// The same board as [`moved`], seen by a second snake of ours.
+    fn moved_as(id: &str, turn: u16, mv: Move) -> Entry {
+        Entry::Move {
+            request: request(id, turn),
+            strategy: "scored".to_string(),
+            mv,
+            shout: String::new(),
+            trace: None,
+        }
+    }
+
+    #[test]
+    fn diff_and_golden() {
+        let strategy =
+            |_: &str| -> Arc<dyn Strategy> { Arc::new(Greedy::scored(1.5, Weights::default())) };
+        let entries = vec![
+            Entry::Start {
+                request: request("you", 0),
+            },
+            moved(1, Move::Right),
+            moved(2, Move::Down),
+        ];
+        let golden = Golden {
+            expect: vec![Expectation {
+                turn: 2,
+                snake: None,
+                moves: vec![Move::Down],
+                note: Some("pretend down was right all along".to_string()),
+            }],
+        };
+
+        let report = replay(&entries, &strategy, &golden).expect("must replay");
+
+        assert_eq!(report.game, "test");
+        assert_eq!(report.turns.len(), 2);
+        assert_eq!(report.turns[0].chosen, Some(Move::Right));
+        assert_eq!(
+            report.changed().map(|t| t.turn).collect::<Vec<_>>(),
+            vec![2]
+        );
+        assert_eq!(
+            report.failures().map(|t| t.turn).collect::<Vec<_>>(),
+            vec![2]
+        );
+
+        let missing = Golden {
+            expect: vec![Expectation {
+                turn: 3,
+                snake: None,
+                moves: vec![Move::Up],
+                note: None,
+            }],
+        };
+        assert!(replay(&entries, &strategy, &missing).is_err());
+    }
+
+    #[test]
+    fn golden_per_snake() {
+        let strategy =
+            |_: &str| -> Arc<dyn Strategy> { Arc::new(Greedy::scored(1.5, Weights::default())) };
+        let entries = vec![moved(2, Move::Right), moved_as("other", 2, Move::Right)];
+        let expect = |snake: Option<&str>| Expectation {
+            turn: 2,
+            snake: snake.map(str::to_string),
+            moves: vec![Move::Down],
+            note: None,
+        };
+
+        let golden = Golden {
+            expect: vec![expect(Some("other"))],
+        };
+        let report = replay(&entries, &strategy, &golden).expect("must replay");
+        assert_eq!(
+            report
+                .failures()
+                .map(|t| t.snake.as_str())
+                .collect::<Vec<_>>(),
+            vec!["other"]
+        );
+
+        let vague = Golden {
+            expect: vec![expect(None)],
+        };
+        assert!(replay(&entries, &strategy, &vague).is_err());
+
+        let unknown = Golden {
+            expect: vec![expect(Some("nobody"))],
+        };
+        assert!(replay(&entries, &strategy, &unknown).is_err());
+    }
+
+    #[test]
+    fn load_golden() {
+        let golden: Golden = toml::from_str(
+            r#"
+            [[expect]]
+            turn = 12
+            moves = ["left", "up"]
+            note = "down walks into a dead end"
+            "#,
+        )
+        .expect("must parse");
+
+        assert_eq!(
+            golden.get(12, "you").map(|e| e.moves.clone()),
+            Some(vec![Move::Left, Move::Up])
+        );
+        assert_eq!(golden.get(13, "you"), None);
+
+        let golden: Golden = toml::from_str(
+            r#"
+            [[expect]]
+            turn = 12
+            snake = "other"
+            moves = ["left"]
+            "#,
+        )
+        .expect("must parse");
+
+        assert!(golden.get(12, "other").is_some());
+        assert_eq!(golden.get(12, "you"), None);
+    }
+
+    /// Always goes up, noting each call it gets.
+    #[derive(Default)]
+    struct Noted {
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl Noted {
+        fn note(&self, call: &str, state: &State) {
+            self.calls
+                .lock()
+                .expect("not poisoned")
+                .push(format!("{} {} {}", call, state.you.id, state.turn));
+        }
+    }
+
+    impl Strategy for Noted {
+        fn name(&self) -> &str {
+            "noted"
+        }
+
+        fn start(&self, state: &State, _: &mut Session) {
+            self.note("start", state);
+        }
+
+        fn decide(&self, state: &State, _: &mut Session, _: Deadline) -> Result<Decision> {
+            self.note("decide", state);
+            Ok(Decision::new(Move::Up))
+        }
+
+        fn end(&self, state: &State, _: &mut Session) {
+            self.note("end", state);
+        }
+    }
+
+    #[test]
+    fn start_and_end() {
+        let noted = Arc::new(Noted::default());
+        let strategy = |_: &str| -> Arc<dyn Strategy> { noted.clone() };
+        let entries = vec![
+            Entry::Start {
+                request: request("you", 0),
+            },
+            moved(1, Move::Up),
+            moved_as("other", 1, Move::Up),
+            moved(2, Move::Up),
+            Entry::End {
+                request: request("you", 3),
+            },
+        ];
+
+        replay(&entries, &strategy, &Golden::default()).expect("must replay");
+
+        assert_eq!(
+            *noted.calls.lock().expect("not poisoned"),
+            vec![
+                "start you 1",
+                "decide you 1",
+                "start other 1",
+                "decide other 1",
+                "decide you 2",
+                "end you 3",
+            ]
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+
+use rand::{seq::SliceRandom, thread_rng, Rng};
+
+use crate::game::{Move, Snake, State};
+
+use super::Deadline;
+
+const EXPLORATION: f32 = 1.4;
+const ROLLOUT_DEPTH: usize = 25;
+
+/// Runs decoupled UCT until the deadline, returning the most visited move for `you` and the
+/// number of iterations that were run.
+///
+/// Every snake picks its move independently at each node from its own statistics, so the tree
+/// handles simultaneous moves for any number of snakes.
+pub fn search(state: &State, deadline: Deadline) -> Option<(Move, usize)> {
+    let (mv, iterations) = search_with(state, deadline, usize::MAX, &mut thread_rng())?;
+
+    log::debug!(
+        target: "search",
+        "game={} turn={} search=mcts iterations={} chosen={:?}",
+        state.game.id,
+        state.turn,
+        iterations,
+        mv
+    );
+
+    Some((mv, iterations))
+}
+
+/// Like `search`, but stops after at most `budget` iterations even if there's time left.
+fn search_with<R: Rng>(
+    state: &State,
+    deadline: Deadline,
+    budget: usize,
+    rng: &mut R,
+) -> Option<(Move, usize)> {
+    if !state.board.snakes.contains(&state.you) {
+        return None;
+    }
+
+    // Every iteration would stop at the root, so there's nothing to learn.
+    if state.is_over() {
+        return safe_moves(state, &state.you).first().map(|mv| (*mv, 0));
+    }
+
+    let mut tree = Tree {
+        nodes: vec![Node::new(state)],
+    };
+
+    let mut iterations = 0;
+    while iterations < budget && !deadline.expired() {
+        tree.iterate(state, rng);
+        iterations += 1;
+    }
+
+    let root = &tree.nodes[0];
+    let index = root.snakes.iter().position(|id| id == &state.you.id)?;
+    let mv = root.moves[index]
+        .iter()
+        .zip(root.stats[index].iter())
+        .max_by_key(|(_, stat)| stat.visits)
+        .map(|(mv, _)| *mv)?;
+
+    Some((mv, iterations))
+}
+
+#[derive(Default, Clone, Copy)]
+struct Stat {
+    visits: u32,
+    reward: f32,
+}
+
+struct Node {
+    snakes: Vec<String>,
+    moves: Vec<Vec<Move>>,
+    stats: Vec<Vec<Stat>>,
+    children: HashMap<Vec<usize>, usize>,
+    visits: u32,
+}
+
+impl Node {
+    fn new(state: &State) -> Node {
+        let moves: Vec<Vec<Move>> = state
+            .board
+            .snakes
+            .iter()
+            .map(|snake| safe_moves(state, snake))
+            .collect();
+
+        Node {
+            snakes: state.board.snakes.iter().map(|s| s.id.clone()).collect(),
+            stats: moves
+                .iter()
+                .map(|m| vec![Stat::default(); m.len()])
+                .collect(),
+            moves,
+            children: HashMap::new(),
+            visits: 0,
+        }
+    }
+
+    /// Picks a move index for each snake independently, trying unvisited moves first.
+    fn select<R: Rng>(&self, rng: &mut R) -> Vec<usize> {
+        self.stats
+            .iter()
+            .map(|stats| {
+                let unvisited: Vec<usize> =
+                    (0..stats.len()).filter(|i| stats[*i].visits == 0).collect();
+
+                if let Some(index) = unvisited.choose(rng) {
+                    return *index;
+                }
+
+                let total = (self.visits as f32).ln();
+                (0..stats.len())
+                    .max_by(|a, b| ucb(&stats[*a], total).total_cmp(&ucb(&stats[*b], total)))
+                    .unwrap_or(0)
+            })
+            .collect()
+    }
+
+    fn joint_moves(&self, choice: &[usize]) -> HashMap<String, Move> {
+        self.snakes
+            .iter()
+            .zip(choice.iter())
+            .enumerate()
+            .map(|(i, (id, index))| (id.clone(), self.moves[i][*index]))
+            .collect()
+    }
+}
+
+fn ucb(stat: &Stat, log_total: f32) -> f32 {
+    let visits = stat.visits as f32;
+    stat.reward / visits + EXPLORATION * (log_total / visits).sqrt()
+}
+
+struct Tree {
+    nodes: Vec<Node>,
+}
+
+impl Tree {
+    fn iterate<R: Rng>(&mut self, root: &State, rng: &mut R) {
+        let mut state = root.clone();
+        let mut node = 0;
+        let mut path: Vec<(usize, Vec<usize>)> = Vec::new();
+
+        let rewards = loop {
+            if state.is_over() {
+                break rewards(&state);
+            }
+
+            let choice = self.nodes[node].select(rng);
+            state = state.apply_moves(&self.nodes[node].joint_moves(&choice));
+            path.push((node, choice.clone()));
+
+            match self.nodes[node].children.get(&choice) {
+                Some(child) => node = *child,
+                None => {
+                    let child = self.nodes.len();
+                    self.nodes.push(Node::new(&state));
+                    self.nodes[node].children.insert(choice, child);
+                    break rollout(state, rng);
+                }
+            }
+        };
+
+        for (index, choice) in path {
+            let node = &mut self.nodes[index];
+            node.visits += 1;
+
+            for (i, id) in node.snakes.iter().enumerate() {
+                let stat = &mut node.stats[i][choice[i]];
+                stat.visits += 1;
+                stat.reward += rewards.get(id).copied().unwrap_or(0.0);
+            }
+        }
+    }
+}
+
+fn rollout<R: Rng>(mut state: State, rng: &mut R) -> HashMap<String, f32> {
+    for _ in 0..ROLLOUT_DEPTH {
+        if state.is_over() {
+            break;
+        }
+
+        let moves = state
+            .board
+            .snakes
+            .iter()
+            .filter_map(|snake| {
+                let mv = *safe_moves(&state, snake).choose(rng)?;
+                Some((snake.id.clone(), mv))
+            })
+            .collect();
+
+        state = state.apply_moves(&moves);
+    }
+
+    rewards(&state)
+}
+
+/// Survivors split the reward, so outlasting everyone is worth the most.
+fn rewards(state: &State) -> HashMap<String, f32> {
+    let share = 1.0 / state.board.snakes.len().max(1) as f32;
+
+    state
+        .board
+        .snakes
+        .iter()
+        .map(|snake| (snake.id.clone(), share))
+        .collect()
+}
+
+/// Moves that don't immediately run into a wall or a body, or every move except doubling back
+/// if there are none.
+fn safe_moves(state: &State, snake: &Snake) -> Vec<Move> {
+    let moves: Vec<Move> = Move::all()
+        .into_iter()
+        .filter(|mv| snake.neck() != Some(&state.board.shift(&snake.head, mv)))
+        .collect();
+
+    let safe: Vec<Move> = moves
+        .iter()
+        .copied()
+        .filter(|mv| {
+            let point = state.board.shift(&snake.head, mv);
+            state.board.in_bounds(&point)
+                && !state
+                    .board
+                    .snakes
+                    .iter()
+                    .any(|other| other.at(&point, true))
+        })
+        .collect();
+
+    if safe.is_empty() {
+        moves
+    } else {
+        safe
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    /// One snake alone in a corner, so the game is already over.
+    fn alone() -> State {
+        "
+            6  . . . . . . .
+            5  . . . . . . .
+            4  . . . . . . .
+            3  . . . . . . .
+            2  . . . . . . .
+            1  A . . . . . .
+            0  a . . . . . .
+        "
+        .parse()
+        .expect("must parse")
+    }
+
+    #[test]
+    fn avoids_dead_end() {
+        let state: State = "
+            6  . . . . . . C
+            5  . . . . . . c
+            4  b b b . . . .
+            3  b b . . . . .
+            2  . b b b . . .
+            1  A . . B . . .
+            0  a a . . . . .
+        "
+        .parse()
+        .expect("must parse");
+
+        let (mv, iterations) = search_with(
+            &state,
+            Deadline::after(Duration::from_secs(600)),
+            1000,
+            &mut StdRng::seed_from_u64(7),
+        )
+        .expect("must be some");
+
+        assert_eq!(mv, Move::Right);
+        assert_eq!(iterations, 1000);
+    }
+
+    #[test]
+    fn already_over() {
+        let state = alone();
+        assert!(state.is_over());
+
+        let (mv, iterations) = search_with(
+            &state,
+            Deadline::after(Duration::from_secs(600)),
+            usize::MAX,
+            &mut StdRng::seed_from_u64(7),
+        )
+        .expect("must be some");
+
+        assert_eq!(mv, Move::Up);
+        assert_eq!(iterations, 0);
+    }
+
+    #[test]
+    fn requires_you() {
+        let mut state = alone();
+        state.board.snakes.clear();
+
+        assert!(search(&state, Deadline::after(Duration::from_millis(10))).is_none());
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use crate::game::{Move, Snake, State};
+
+use super::Deadline;
+
+const MAX_DEPTH: usize = 64;
+const WIN: i32 = 1_000_000;
+
+/// Runs an iterative-deepening alpha-beta search for 1v1 games, returning the best move from the
+/// deepest depth that finished before the deadline along with that depth.
+///
+/// The opponent is assumed to pick its move knowing ours, which keeps simultaneous moves
+/// pessimistic. Returns `None` for games that aren't 1v1 or when not even one ply completes.
+pub fn search(state: &State, deadline: Deadline) -> Option<(Move, usize)> {
+    let opponent = opponent(state)?;
+    let search = Search {
+        you: state.you.id.clone(),
+        opponent: opponent.id.clone(),
+        deadline,
+    };
+
+    let mut best: Option<(Move, usize)> = None;
+    for depth in 1..=MAX_DEPTH {
+        match search.root(state, depth, best.map(|(mv, _)| mv)) {
+            Some(mv) => best = Some((mv, depth)),
+            None => break,
+        }
+    }
+
+    if let Some((mv, depth)) = best {
+        log::debug!(
+            target: "search",
+            "game={} turn={} search=minimax depth={} chosen={:?}",
+            state.game.id,
+            state.turn,
+            depth,
+            mv
+        );
+    }
+
+    best
+}
+
+fn opponent(state: &State) -> Option<&Snake> {
+    let mut others = state
+        .board
+        .snakes
+        .iter()
+        .filter(|snake| snake.id != state.you.id);
+
+    match (others.next(), others.next()) {
+        (Some(opponent), None) if state.board.snakes.contains(&state.you) => Some(opponent),
+        _ => None,
+    }
+}
+
+struct Search {
+    you: String,
+    opponent: String,
+    deadline: Deadline,
+}
+
+impl Search {
+    fn root(&self, state: &State, depth: usize, previous: Option<Move>) -> Option<Move> {
+        let mut moves = candidates(state, &state.you);
+
+        // Searching last iteration's best move first makes for much better pruning.
+        if let Some(previous) = previous {
+            moves.sort_by_key(|mv| *mv != previous);
+        }
+
+        let mut alpha = -i32::MAX;
+        let mut best = None;
+        for mv in moves {
+            let value = self.min(state, mv, depth, alpha, i32::MAX)?;
+            if best.is_none() || value > alpha {
+                alpha = value;
+                best = Some(mv);
+            }
+        }
+
+        best
+    }
+
+    fn max(&self, state: &State, depth: usize, mut alpha: i32, beta: i32) -> Option<i32> {
+        if self.deadline.expired() {
+            return None;
+        }
+
+        if let Some(value) = self.terminal(state, depth) {
+            return Some(value);
+        }
+
+        if depth == 0 {
+            return Some(self.evaluate(state));
+        }
+
+        let you = self.snake(state, &self.you)?;
+        for mv in candidates(state, you) {
+            alpha = alpha.max(self.min(state, mv, depth, alpha, beta)?);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        Some(alpha)
+    }
+
+    fn min(&self, state: &State, mv: Move, depth: usize, alpha: i32, mut beta: i32) -> Option<i32> {
+        let opponent = self.snake(state, &self.opponent)?;
+
+        for opponent_mv in candidates(state, opponent) {
+            let moves =
+                HashMap::from([(self.you.clone(), mv), (self.opponent.clone(), opponent_mv)]);
+
+            let next = state.apply_moves(&moves);
+            beta = beta.min(self.max(&next, depth - 1, alpha, beta)?);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        Some(beta)
+    }
+
+    fn terminal(&self, state: &State, depth: usize) -> Option<i32> {
+        let you_alive = self.snake(state, &self.you).is_some();
+        let opponent_alive = self.snake(state, &self.opponent).is_some();
+
+        // Prefer quick wins and slow losses.
+        let depth = depth as i32;
+        match (you_alive, opponent_alive) {
+            (true, true) => None,
+            (true, false) => Some(WIN + depth),
+            (false, true) => Some(-WIN - depth),
+            (false, false) => Some(0),
+        }
+    }
+
+    fn evaluate(&self, state: &State) -> i32 {
+        let (you, opponent) = match (
+            self.snake(state, &self.you),
+            self.snake(state, &self.opponent),
+        ) {
+            (Some(you), Some(opponent)) => (you, opponent),
+            _ => return 0,
+        };
+
+        let territory = state.board.territory();
+        let space = territory.size(&you.id) as i32 - territory.size(&opponent.id) as i32;
+        let length = you.length() as i32 - opponent.length() as i32;
+        let health = you.health as i32 - opponent.health as i32;
+
+        space * 10 + length * 30 + health / 10
+    }
+
+    fn snake<'a>(&self, state: &'a State, id: &str) -> Option<&'a Snake> {
+        state.board.snakes.iter().find(|snake| snake.id == id)
+    }
+}
+
+/// Every move except doubling back onto the neck, which is never legal.
+fn candidates(state: &State, snake: &Snake) -> Vec<Move> {
+    Move::all()
+        .into_iter()
+        .filter(|mv| snake.neck() != Some(&state.board.shift(&snake.head, mv)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn avoids_dead_end() {
+        let state: State = "
+            4  b b b . .
+            3  b b . . .
+            2  . b b b .
+            1  A . . B .
+            0  a a . . .
+        "
+        .parse()
+        .expect("must parse");
+
+        let (mv, depth) =
+            search(&state, Deadline::after(Duration::from_millis(100))).expect("must be some");
+
+        assert_eq!(mv, Move::Right);
+        assert!(depth >= 2);
+    }
+
+    #[test]
+    fn only_1v1() {
+        let state: State = "
+            4  . . . . .
+            3  . . . . .
+            2  . . . . .
+            1  A . B . C
+            0  a . b . c
+        "
+        .parse()
+        .expect("must parse");
+
+        assert!(search(&state, Deadline::after(Duration::from_millis(100))).is_none());
+    }
+}
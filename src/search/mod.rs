@@ -0,0 +1,56 @@
+pub mod mcts;
+pub mod minimax;
+
+use std::time::{Duration, Instant};
+
+use crate::game::Game;
+
+/// Time we leave for the network round trip when budgeting a move.
+const LATENCY_BUFFER_MS: u64 = 200;
+
+/// Time we leave for the network round trip when cutting off a move that's run over its budget.
+/// Less than `LATENCY_BUFFER_MS`, so a search finishing on time is never cut off.
+const RESPONSE_BUFFER_MS: u64 = 100;
+
+/// The smallest budget we'll give a search, even on games with very short timeouts.
+const MIN_BUDGET_MS: u64 = 20;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Deadline {
+    at: Instant,
+}
+
+impl Deadline {
+    pub fn after(budget: Duration) -> Deadline {
+        Deadline {
+            at: Instant::now() + budget,
+        }
+    }
+
+    pub fn for_game(game: &Game) -> Deadline {
+        let budget = game
+            .timeout
+            .saturating_sub(LATENCY_BUFFER_MS)
+            .max(MIN_BUDGET_MS);
+
+        Deadline::after(Duration::from_millis(budget))
+    }
+
+    /// The last moment a move can still be answered in time, whatever the strategy is doing.
+    pub fn response(game: &Game) -> Deadline {
+        let budget = game
+            .timeout
+            .saturating_sub(RESPONSE_BUFFER_MS)
+            .max(MIN_BUDGET_MS);
+
+        Deadline::after(Duration::from_millis(budget))
+    }
+
+    pub fn expired(&self) -> bool {
+        Instant::now() >= self.at
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.at.saturating_duration_since(Instant::now())
+    }
+}
@@ -0,0 +1,311 @@
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::game::State;
+use crate::record::Entry;
+
+/// How many of a game's latest moves are kept for the viewer.
+const HISTORY: usize = 500;
+
+/// A game id and one of our snakes' ids in it.
+pub type Key = (String, String);
+
+/// Everything we remember about one of our snakes over the course of a game.
+pub struct Session {
+    pub game: String,
+    pub snake: String,
+    pub started: Instant,
+    /// Moves decided so far, and the time they took.
+    pub moves: usize,
+    pub decide_time: Duration,
+    pub slowest: Duration,
+    memory: HashMap<TypeId, Box<dyn Any + Send>>,
+}
+
+impl Session {
+    pub fn new(state: &State) -> Session {
+        Session {
+            game: state.game.id.clone(),
+            snake: state.you.id.clone(),
+            started: Instant::now(),
+            moves: 0,
+            decide_time: Duration::ZERO,
+            slowest: Duration::ZERO,
+            memory: HashMap::new(),
+        }
+    }
+
+    /// Whatever a strategy wants to keep between turns, one value per type, created on first use.
+    pub fn memory<T: Any + Send + Default>(&mut self) -> &mut T {
+        self.memory
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(T::default()))
+            .downcast_mut::<T>()
+            .expect("memory is keyed by its type")
+    }
+
+    pub fn timed(&mut self, elapsed: Duration) {
+        self.moves += 1;
+        self.decide_time += elapsed;
+        self.slowest = self.slowest.max(elapsed);
+    }
+
+    pub fn average_decide_time(&self) -> Option<Duration> {
+        if self.moves == 0 {
+            None
+        } else {
+            Some(self.decide_time / self.moves as u32)
+        }
+    }
+}
+
+/// The sessions of every game in progress, keyed by game and snake id since we may have more
+/// than one snake in the same game. When each was last seen and the moves played are kept
+/// alongside rather than in the session, so neither waits on a session busy deciding a move.
+/// Once a game ends its moves stay around for another `ttl`, so the viewer can still show it.
+pub struct Sessions {
+    ttl: Duration,
+    sessions: Mutex<HashMap<Key, Tracked>>,
+    finished: Mutex<HashMap<Key, (Instant, VecDeque<Entry>)>>,
+}
+
+struct Tracked {
+    session: Arc<Mutex<Session>>,
+    last_seen: Instant,
+    history: VecDeque<Entry>,
+}
+
+impl Tracked {
+    fn new(session: Arc<Mutex<Session>>) -> Tracked {
+        Tracked {
+            session,
+            last_seen: Instant::now(),
+            history: VecDeque::new(),
+        }
+    }
+}
+
+impl Sessions {
+    /// Sessions not seen for `ttl` are dropped, for games whose `/end` never arrives.
+    pub fn new(ttl: Duration) -> Sessions {
+        Sessions {
+            ttl,
+            sessions: Mutex::new(HashMap::new()),
+            finished: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// How often to sweep, so no session outlives its `ttl` by much.
+    pub fn sweep_interval(&self) -> Duration {
+        (self.ttl / 2).max(Duration::from_secs(1))
+    }
+
+    /// Starts a fresh session for the game, replacing any left over.
+    pub fn start(&self, state: &State) -> Arc<Mutex<Session>> {
+        let session = Arc::new(Mutex::new(Session::new(state)));
+        self.map().insert(key(state), Tracked::new(session.clone()));
+        session
+    }
+
+    /// The game's session, starting one if we missed `/start`, say after a restart.
+    pub fn get(&self, state: &State) -> Arc<Mutex<Session>> {
+        let mut sessions = self.map();
+        let tracked = sessions
+            .entry(key(state))
+            .or_insert_with(|| Tracked::new(Arc::new(Mutex::new(Session::new(state)))));
+
+        tracked.last_seen = Instant::now();
+        tracked.session.clone()
+    }
+
+    /// Keeps a move as it was played, for the viewer, forgetting the oldest past `HISTORY`.
+    pub fn played(&self, state: &State, entry: Entry) {
+        if let Some(tracked) = self.map().get_mut(&key(state)) {
+            if tracked.history.len() == HISTORY {
+                tracked.history.pop_front();
+            }
+            tracked.history.push_back(entry);
+        }
+    }
+
+    /// The latest moves played in a game, in progress or finished, oldest first.
+    pub fn history(&self, game: &str, snake: &str) -> Option<Vec<Entry>> {
+        let key = (game.to_string(), snake.to_string());
+        if let Some(tracked) = self.map().get(&key) {
+            return Some(tracked.history.iter().cloned().collect());
+        }
+
+        self.finished_map()
+            .get(&key)
+            .map(|(_, history)| history.iter().cloned().collect())
+    }
+
+    /// The game and snake ids of every session, sorted.
+    pub fn keys(&self) -> Vec<Key> {
+        let mut keys: Vec<Key> = self.map().keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    /// The game and snake ids of every game ended within the last `ttl`, sorted.
+    pub fn finished(&self) -> Vec<Key> {
+        let mut keys: Vec<Key> = self.finished_map().keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    /// Takes the game's session out, keeping its moves for the viewer. `None` if it was never
+    /// started or has expired, say after a restart, in which case there's nothing to wrap up but
+    /// a fresh `Session` for the game.
+    pub fn end(&self, state: &State) -> Option<Arc<Mutex<Session>>> {
+        let key = key(state);
+        let tracked = self.map().remove(&key)?;
+        self.finished_map()
+            .insert(key, (Instant::now(), tracked.history));
+        Some(tracked.session)
+    }
+
+    /// Drops expired sessions, returning how many there were, along with the moves of games
+    /// ended over `ttl` ago.
+    pub fn sweep(&self) -> usize {
+        let now = Instant::now();
+        self.finished_map()
+            .retain(|_, (ended, _)| now.duration_since(*ended) < self.ttl);
+
+        let mut sessions = self.map();
+        let before = sessions.len();
+
+        sessions.retain(|_, tracked| now.duration_since(tracked.last_seen) < self.ttl);
+        before - sessions.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn map(&self) -> MutexGuard<'_, HashMap<Key, Tracked>> {
+        self.sessions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn finished_map(&self) -> MutexGuard<'_, HashMap<Key, (Instant, VecDeque<Entry>)>> {
+        self.finished
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Locks a session, carrying on even if a strategy panicked while holding it.
+pub fn lock(session: &Mutex<Session>) -> MutexGuard<'_, Session> {
+    session
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn key(state: &State) -> Key {
+    (state.game.id.clone(), state.you.id.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(game: &str) -> State {
+        let mut state: State = "A a".parse().expect("must parse");
+        state.game.id = game.to_string();
+        state
+    }
+
+    #[test]
+    fn memory() {
+        let mut session = Session::new(&state("a"));
+
+        *session.memory::<usize>() += 2;
+        session.memory::<Vec<u8>>().push(1);
+        *session.memory::<usize>() += 3;
+
+        assert_eq!(*session.memory::<usize>(), 5);
+        assert_eq!(session.memory::<Vec<u8>>(), &vec![1]);
+
+        assert_eq!(session.average_decide_time(), None);
+        session.timed(Duration::from_millis(10));
+        session.timed(Duration::from_millis(30));
+        assert_eq!(
+            session.average_decide_time(),
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(session.slowest, Duration::from_millis(30));
+    }
+
+    #[test]
+    fn lifecycle() {
+        let sessions = Sessions::new(Duration::from_millis(50));
+
+        *lock(&sessions.start(&state("a"))).memory::<usize>() = 1;
+        *lock(&sessions.get(&state("a"))).memory::<usize>() += 1;
+        assert_eq!(*lock(&sessions.get(&state("a"))).memory::<usize>(), 2);
+
+        // Missed the start.
+        let busy = sessions.get(&state("b"));
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(
+            sessions.keys(),
+            vec![
+                ("a".to_string(), "a".to_string()),
+                ("b".to_string(), "a".to_string())
+            ]
+        );
+        assert!(sessions.history("b", "a").is_some());
+        assert!(sessions.history("b", "b").is_none());
+
+        assert!(sessions.end(&state("a")).is_some());
+        assert!(sessions.end(&state("a")).is_none());
+        assert_eq!(sessions.len(), 1);
+
+        // The ended game's moves outlive its session.
+        assert!(sessions.history("a", "a").is_some());
+        assert_eq!(
+            sessions.finished(),
+            vec![("a".to_string(), "a".to_string())]
+        );
+
+        assert_eq!(sessions.sweep(), 0);
+        std::thread::sleep(Duration::from_millis(60));
+
+        // A session busy with a move doesn't hold up the sweep.
+        let _busy = lock(&busy);
+        assert_eq!(sessions.sweep(), 1);
+        assert!(sessions.is_empty());
+        assert!(sessions.finished().is_empty());
+        assert!(sessions.history("a", "a").is_none());
+    }
+
+    #[test]
+    fn history() {
+        let sessions = Sessions::new(Duration::from_secs(60));
+        let played = |turn: usize| Entry::End {
+            request: serde_json::json!({ "turn": turn }),
+        };
+
+        // Nothing kept for a game we have no session for.
+        sessions.played(&state("a"), played(0));
+        assert_eq!(sessions.history("a", "a"), None);
+
+        sessions.start(&state("a"));
+        for turn in 0..=HISTORY {
+            sessions.played(&state("a"), played(turn));
+        }
+
+        let history = sessions.history("a", "a").expect("started");
+        assert_eq!(history.len(), HISTORY);
+        assert_eq!(history[0], played(1));
+        assert_eq!(history[HISTORY - 1], played(HISTORY));
+    }
+}
@@ -0,0 +1,285 @@
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Move, Point, State};
+
+/// A soft preference for where a move lands, scored on a rough 0 to 1 scale before weighting.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Heuristic {
+    Pocket,
+    Territory,
+    Food,
+    Threat,
+    Kill,
+    Tail,
+}
+
+impl Heuristic {
+    pub fn all() -> Vec<Heuristic> {
+        vec![
+            Heuristic::Pocket,
+            Heuristic::Territory,
+            Heuristic::Food,
+            Heuristic::Threat,
+            Heuristic::Kill,
+            Heuristic::Tail,
+        ]
+    }
+}
+
+/// Flags default to `Weights::default()`, so the two can't drift apart.
+#[derive(Args, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Weights {
+    /// Weight of the space reachable after a move, counting cells that tails retreat from.
+    #[clap(long = "pocket-weight", default_value_t = Weights::default().pocket)]
+    pub pocket: f32,
+
+    /// Weight of the cells we'd reach before any other snake after a move.
+    #[clap(long = "territory-weight", default_value_t = Weights::default().territory)]
+    pub territory: f32,
+
+    /// Weight of closing in on food, which only counts when hungry or competing for length.
+    #[clap(long = "food-weight", default_value_t = Weights::default().food)]
+    pub food: f32,
+
+    /// Weight of moving next to the head of a snake at least as long as us.
+    #[clap(long = "threat-weight", default_value_t = Weights::default().threat)]
+    pub threat: f32,
+
+    /// Weight of moving next to the head of a shorter snake.
+    #[clap(long = "kill-weight", default_value_t = Weights::default().kill)]
+    pub kill: f32,
+
+    /// Weight of closing in on our own tail.
+    #[clap(long = "tail-weight", default_value_t = Weights::default().tail)]
+    pub tail: f32,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Weights {
+            pocket: 10.0,
+            territory: 5.0,
+            food: 3.0,
+            threat: 5.0,
+            kill: 2.0,
+            tail: 1.0,
+        }
+    }
+}
+
+impl Weights {
+    pub fn get(&self, heuristic: Heuristic) -> f32 {
+        match heuristic {
+            Heuristic::Pocket => self.pocket,
+            Heuristic::Territory => self.territory,
+            Heuristic::Food => self.food,
+            Heuristic::Threat => self.threat,
+            Heuristic::Kill => self.kill,
+            Heuristic::Tail => self.tail,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Score {
+    #[serde(rename = "move")]
+    pub mv: Move,
+    pub total: f32,
+    /// Each heuristic's weighted contribution to the total.
+    pub components: Vec<(Heuristic, f32)>,
+}
+
+/// Scores each move by the weighted sum of every heuristic.
+pub fn evaluate(
+    state: &State,
+    moves: &[Move],
+    weights: &Weights,
+    hunger_coefficient: f32,
+) -> Vec<Score> {
+    let evaluator = Evaluator::new(state, hunger_coefficient);
+
+    moves
+        .iter()
+        .map(|mv| {
+            let point = state.board.shift(&state.you.head, mv);
+            let components: Vec<(Heuristic, f32)> = Heuristic::all()
+                .into_iter()
+                .map(|heuristic| {
+                    // Some heuristics are expensive, so don't bother when they'd count for nothing.
+                    let weight = weights.get(heuristic);
+                    let score = if weight == 0.0 {
+                        0.0
+                    } else {
+                        evaluator.score(heuristic, &point) * weight
+                    };
+
+                    (heuristic, score)
+                })
+                .collect();
+
+            Score {
+                mv: *mv,
+                total: components.iter().map(|(_, score)| score).sum(),
+                components,
+            }
+        })
+        .collect()
+}
+
+/// The highest scoring move. Ties go to whichever move was scored first, so the same state always
+/// gets the same answer.
+pub fn best(scores: &[Score]) -> Option<Move> {
+    scores
+        .iter()
+        .fold(None, |best: Option<&Score>, score| match best {
+            Some(best) if best.total >= score.total => Some(best),
+            _ => Some(score),
+        })
+        .map(|score| score.mv)
+}
+
+struct Evaluator<'a> {
+    state: &'a State,
+    hungry_for: Option<&'a Point>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn new(state: &'a State, hunger_coefficient: f32) -> Evaluator<'a> {
+        let hungry_for = state.board.closest_food(&state.you.head).filter(|food| {
+            state.need_food(food.distance(&state.you.head), hunger_coefficient)
+                || state.compete_for_biggest()
+        });
+
+        Evaluator { state, hungry_for }
+    }
+
+    fn score(&self, heuristic: Heuristic, point: &Point) -> f32 {
+        let state = self.state;
+        let area = (state.board.width * state.board.height).max(1) as f32;
+
+        match heuristic {
+            Heuristic::Pocket => match state.board.timed_pocket_at(point, 1) {
+                Some(pocket) => pocket.area as f32 / area,
+                None => 0.0,
+            },
+            Heuristic::Territory => {
+                if !state.board.in_bounds(point) {
+                    return 0.0;
+                }
+
+                let mut board = state.board.clone();
+                if let Some(you) = board.snakes.iter_mut().find(|s| s.id == state.you.id) {
+                    you.head = *point;
+                    you.body.insert(0, *point);
+                    you.body.pop();
+                }
+
+                board.territory().size(&state.you.id) as f32 / area
+            }
+            Heuristic::Food => match self.hungry_for {
+                Some(food) => {
+                    let span = (state.board.width + state.board.height) as f32;
+                    1.0 - food.distance(point) as f32 / span
+                }
+                None => 0.0,
+            },
+            Heuristic::Threat => {
+                if state.threatened(point) {
+                    -1.0
+                } else {
+                    0.0
+                }
+            }
+            Heuristic::Kill => {
+                if state.kill_chance(point) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Heuristic::Tail => {
+                let tail = state.you.tail();
+                if point.distance(tail) < state.you.head.distance(tail) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weighted_components() {
+        let state: State = "
+            4  . . . . .
+            3  . . . . .
+            2  . . . . .
+            1  a A . . $
+            0  a . . . .
+            A: health=5
+        "
+        .parse()
+        .expect("must parse");
+        let weights = Weights {
+            pocket: 0.0,
+            territory: 0.0,
+            food: 2.0,
+            threat: 0.0,
+            kill: 0.0,
+            tail: 0.0,
+        };
+
+        let scores = evaluate(&state, &[Move::Up, Move::Right], &weights, 1.5);
+
+        assert_eq!(scores[0].total, 2.0 * (1.0 - 4.0 / 10.0));
+        assert_eq!(scores[1].total, 2.0 * (1.0 - 2.0 / 10.0));
+        assert_eq!(best(&scores), Some(Move::Right));
+    }
+
+    #[test]
+    fn deterministic_ties() {
+        let state: State = "
+            4  . . . . .
+            3  . . . . .
+            2  . . . . .
+            1  a A . . .
+            0  a . . . .
+            A: health=5
+        "
+        .parse()
+        .expect("must parse");
+        let weights = Weights {
+            pocket: 0.0,
+            territory: 0.0,
+            food: 0.0,
+            threat: 0.0,
+            kill: 0.0,
+            tail: 0.0,
+        };
+
+        let scores = evaluate(&state, &Move::all(), &weights, 1.5);
+
+        assert_eq!(best(&scores), Some(Move::Up));
+        assert_eq!(best(&[]), None);
+    }
+
+    #[test]
+    fn default_flags() {
+        #[derive(clap::Parser)]
+        struct Flags {
+            #[clap(flatten)]
+            weights: Weights,
+        }
+
+        let flags = <Flags as clap::Parser>::parse_from(["battlesnake"]);
+        assert_eq!(flags.weights, Weights::default());
+    }
+}
@@ -0,0 +1,143 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::game::{Move, Point, State};
+use crate::search::Deadline;
+use crate::session::Session;
+
+use super::trace::DecisionTrace;
+use super::Strategy;
+
+/// What a move would run into.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    #[serde(rename = "move")]
+    pub mv: Move,
+    pub point: Point,
+    pub in_bounds: bool,
+    /// The id of the snake whose body is there.
+    pub snake: Option<String>,
+    pub food: bool,
+    pub hazard: bool,
+    /// Size of the pocket the move lands in, counting cells tails move out of in time as the
+    /// pocket heuristic does. Nothing if it's occupied or out of bounds.
+    pub pocket: usize,
+    pub threatened: bool,
+    pub kill_chance: bool,
+}
+
+impl Candidate {
+    pub fn all(state: &State) -> Vec<Candidate> {
+        Move::all()
+            .into_iter()
+            .map(|mv| {
+                let point = state.board.shift(&state.you.head, &mv);
+
+                Candidate {
+                    mv,
+                    point,
+                    in_bounds: state.board.in_bounds(&point),
+                    snake: state.board.snake_at(&point).map(|snake| snake.id.clone()),
+                    food: state.board.food_at(&point),
+                    hazard: state.board.hazard_at(&point),
+                    pocket: state
+                        .board
+                        .timed_pocket_at(&point, 1)
+                        .map_or(0, |pocket| pocket.area),
+                    threatened: state.threatened(&point),
+                    kill_chance: state.kill_chance(&point),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Everything behind a strategy's move, for working out why it did something dumb.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub game: String,
+    pub turn: u16,
+    pub strategy: String,
+    pub candidates: Vec<Candidate>,
+    /// Each step of the decision, for strategies that trace theirs.
+    pub trace: Option<DecisionTrace>,
+    #[serde(rename = "move")]
+    pub mv: Move,
+    pub shout: String,
+}
+
+impl Explanation {
+    /// Decides the move afresh, in a session of its own so the game's isn't disturbed, and
+    /// explains that one decision.
+    pub fn new(strategy: &dyn Strategy, state: &State) -> Result<Explanation> {
+        let decision = strategy.decide(
+            state,
+            &mut Session::new(state),
+            Deadline::for_game(&state.game),
+        )?;
+
+        Ok(Explanation {
+            game: state.game.id.clone(),
+            turn: state.turn,
+            strategy: strategy.name().to_string(),
+            candidates: Candidate::all(state),
+            trace: decision.trace,
+            mv: decision.mv,
+            shout: decision.shout,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::strategy::{Filter, Greedy, Minimax, Pipeline, Weights};
+
+    use super::*;
+
+    #[test]
+    fn explain() {
+        let state: State = "
+            . . . . .
+            . . . B .
+            . . . b .
+            A a a b .
+            . . . . .
+        "
+        .parse()
+        .expect("must parse");
+
+        let greedy = Greedy::new(1.5, Pipeline::default(), Weights::default());
+        let explanation = Explanation::new(&greedy, &state).expect("must explain");
+
+        assert_eq!(explanation.strategy, "greedy");
+        assert_eq!(explanation.candidates.len(), 4);
+
+        let right = &explanation.candidates[3];
+        assert_eq!(right.mv, Move::Right);
+        assert_eq!(right.snake.as_deref(), Some("a"));
+        assert_eq!(right.pocket, 0);
+
+        let left = &explanation.candidates[2];
+        assert!(!left.in_bounds);
+
+        let up = &explanation.candidates[0];
+        assert!(!up.threatened);
+        // As the pocket heuristic sees it, every body moves out of the way in time.
+        assert_eq!(up.pocket, 25);
+
+        let trace = explanation.trace.expect("greedy traces");
+        assert_eq!(trace.chosen, Some(explanation.mv));
+        assert_eq!(
+            trace.ruled_out_by(Move::Left).map(|step| step.filter),
+            Some(Filter::InBounds)
+        );
+
+        let minimax = Minimax::new(greedy);
+        assert_eq!(
+            Explanation::new(&minimax, &state)
+                .expect("must explain")
+                .trace,
+            None
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use std::time::Instant;
+
+use anyhow::{anyhow, Result};
+
+use crate::game::State;
+use crate::search::Deadline;
+use crate::session::Session;
+
+use super::evaluation::{self, Weights};
+use super::pipeline::Pipeline;
+use super::trace::DecisionTrace;
+use super::{Decision, Strategy};
+
+/// A single-ply strategy: each step of the pipeline narrows the candidate moves, unless doing so
+/// would leave none, and the weighted heuristics pick among whatever is left.
+#[derive(Debug, Clone)]
+pub struct Greedy {
+    pub hunger_coefficient: f32,
+    pub pipeline: Pipeline,
+    pub weights: Weights,
+    name: &'static str,
+}
+
+impl Greedy {
+    pub fn new(hunger_coefficient: f32, pipeline: Pipeline, weights: Weights) -> Greedy {
+        Greedy {
+            hunger_coefficient,
+            pipeline,
+            weights,
+            name: "greedy",
+        }
+    }
+
+    /// Only filters out moves that are plainly unsafe, leaving every other preference to the
+    /// weighted heuristics.
+    pub fn scored(hunger_coefficient: f32, weights: Weights) -> Greedy {
+        Greedy {
+            hunger_coefficient,
+            pipeline: Pipeline::safety(),
+            weights,
+            name: "scored",
+        }
+    }
+}
+
+impl Greedy {
+    /// Decides a move, keeping track of why.
+    pub fn decide_traced(&self, state: &State) -> DecisionTrace {
+        let started = Instant::now();
+        let mut trace = DecisionTrace::new(state);
+
+        let moves = self
+            .pipeline
+            .run(state, self.hunger_coefficient, &mut trace);
+        trace.scores = evaluation::evaluate(state, &moves, &self.weights, self.hunger_coefficient);
+        trace.chosen = evaluation::best(&trace.scores);
+        trace.elapsed = started.elapsed();
+
+        trace
+    }
+}
+
+impl Strategy for Greedy {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Quick enough never to need the deadline.
+    fn decide(
+        &self,
+        state: &State,
+        _session: &mut Session,
+        _deadline: Deadline,
+    ) -> Result<Decision> {
+        let trace = self.decide_traced(state);
+        let mv = trace
+            .chosen
+            .ok_or_else(|| anyhow!("no moves to choose from"))?;
+
+        let shout = if state
+            .board
+            .food_at(&state.board.shift(&state.you.head, &mv))
+        {
+            "gulp"
+        } else {
+            ""
+        };
+
+        Ok(Decision {
+            mv,
+            shout: shout.to_string(),
+            trace: Some(trace),
+        })
+    }
+}
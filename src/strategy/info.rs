@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+use clap::Args;
+use serde::{Deserialize, Serialize};
+
+use crate::config::load_config;
+
+/// What the snake advertises to the engine on `GET /`.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Info {
+    pub author: String,
+    pub color: String,
+    pub head: String,
+    pub tail: String,
+    pub version: String,
+}
+
+impl Default for Info {
+    fn default() -> Self {
+        Info {
+            author: "broothie".to_string(),
+            color: "#DB5527".to_string(),
+            head: "tongue".to_string(),
+            tail: "block-bum".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// A color in the `#rrggbb` form the engine expects.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct Color(String);
+
+impl FromStr for Color {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let hex = s
+            .strip_prefix('#')
+            .ok_or_else(|| anyhow!("color '{}' must start with '#'", s))?;
+
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!("color '{}' must be '#' followed by six hex digits", s);
+        }
+
+        Ok(Color(s.to_string()))
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        color.0
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Overrides for any part of a snake's `Info`.
+#[derive(Args, Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct Customization {
+    #[clap(long, env = "BATTLESNAKE_AUTHOR")]
+    pub author: Option<String>,
+
+    /// Body color, as `#rrggbb`.
+    #[clap(long, env = "BATTLESNAKE_COLOR")]
+    pub color: Option<Color>,
+
+    /// One of the engine's head customizations.
+    #[clap(long, env = "BATTLESNAKE_HEAD")]
+    pub head: Option<String>,
+
+    /// One of the engine's tail customizations.
+    // Named apart from the tail weight, which would otherwise share its argument id.
+    #[clap(
+        name = "snake-tail",
+        long = "tail",
+        value_name = "TAIL",
+        env = "BATTLESNAKE_TAIL"
+    )]
+    pub tail: Option<String>,
+
+    /// Version advertised to the engine, defaulting to the crate's.
+    // Named apart from clap's own `--version`, which would otherwise share its argument id.
+    #[clap(
+        name = "snake-version",
+        long = "snake-version",
+        value_name = "VERSION",
+        env = "BATTLESNAKE_VERSION"
+    )]
+    pub version: Option<String>,
+}
+
+impl Customization {
+    pub fn apply(&self, info: Info) -> Info {
+        Info {
+            author: self.author.clone().unwrap_or(info.author),
+            color: self.color.clone().map(String::from).unwrap_or(info.color),
+            head: self.head.clone().unwrap_or(info.head),
+            tail: self.tail.clone().unwrap_or(info.tail),
+            version: self.version.clone().unwrap_or(info.version),
+        }
+    }
+}
+
+/// Customizations from a TOML or JSON file: those at the top level apply to every route, and
+/// those under `routes.<strategy>` to just that strategy's routes.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct Appearance {
+    #[serde(flatten)]
+    pub all: Customization,
+    pub routes: HashMap<String, Customization>,
+}
+
+impl Appearance {
+    pub fn load(path: &Path) -> Result<Appearance> {
+        load_config(path, "appearance")
+    }
+
+    /// What a strategy advertises. Its own info is overridden by the file's top level, then by
+    /// `overrides` from the command line or environment, then by the file's settings for the
+    /// strategy's routes, which are the most specific.
+    pub fn info(&self, strategy: &str, info: Info, overrides: &Customization) -> Info {
+        let info = overrides.apply(self.all.apply(info));
+
+        match self.routes.get(strategy) {
+            Some(route) => route.apply(info),
+            None => info,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color() {
+        assert!("#DB5527".parse::<Color>().is_ok());
+        assert!("#db5527".parse::<Color>().is_ok());
+        assert!("DB5527".parse::<Color>().is_err());
+        assert!("#DB552".parse::<Color>().is_err());
+        assert!("#DB552G".parse::<Color>().is_err());
+
+        assert!(toml::from_str::<Customization>(r#"color = "red""#).is_err());
+    }
+
+    #[test]
+    fn appearance() {
+        let appearance: Appearance = toml::from_str(
+            r##"
+            author = "someone"
+            head = "bendr"
+
+            [routes.mcts]
+            color = "#00FF00"
+            head = "smart-caterpillar"
+            "##,
+        )
+        .expect("must parse");
+
+        let overrides = Customization {
+            head: Some("evil".to_string()),
+            color: Some("#0000FF".parse().expect("must parse")),
+            ..Default::default()
+        };
+
+        let greedy = appearance.info("greedy", Info::default(), &overrides);
+        assert_eq!(greedy.author, "someone");
+        assert_eq!(greedy.color, "#0000FF");
+        assert_eq!(greedy.head, "evil");
+        assert_eq!(greedy.tail, "block-bum");
+
+        let mcts = appearance.info("mcts", Info::default(), &overrides);
+        assert_eq!(mcts.author, "someone");
+        assert_eq!(mcts.color, "#00FF00");
+        assert_eq!(mcts.head, "smart-caterpillar");
+    }
+}
@@ -0,0 +1,35 @@
+use anyhow::Result;
+
+use crate::game::State;
+use crate::search::{mcts, Deadline};
+use crate::session::Session;
+
+use super::{Decision, Greedy, Searches, Strategy};
+
+/// Monte Carlo tree search over the simulator, for games with any number of snakes.
+pub struct Mcts {
+    fallback: Greedy,
+}
+
+impl Mcts {
+    pub fn new(fallback: Greedy) -> Mcts {
+        Mcts { fallback }
+    }
+}
+
+impl Strategy for Mcts {
+    fn name(&self) -> &str {
+        "mcts"
+    }
+
+    fn decide(&self, state: &State, session: &mut Session, deadline: Deadline) -> Result<Decision> {
+        let searched = mcts::search(state, deadline);
+        Searches::decide(searched, &self.fallback, state, session, deadline)
+    }
+
+    fn end(&self, state: &State, session: &mut Session) {
+        session
+            .memory::<Searches>()
+            .report(self.name(), state, "iterations");
+    }
+}
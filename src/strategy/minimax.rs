@@ -0,0 +1,35 @@
+use anyhow::Result;
+
+use crate::game::State;
+use crate::search::{minimax, Deadline};
+use crate::session::Session;
+
+use super::{Decision, Greedy, Searches, Strategy};
+
+/// Alpha-beta search for 1v1 games, playing greedily whenever there isn't exactly one opponent.
+pub struct Minimax {
+    fallback: Greedy,
+}
+
+impl Minimax {
+    pub fn new(fallback: Greedy) -> Minimax {
+        Minimax { fallback }
+    }
+}
+
+impl Strategy for Minimax {
+    fn name(&self) -> &str {
+        "minimax"
+    }
+
+    fn decide(&self, state: &State, session: &mut Session, deadline: Deadline) -> Result<Decision> {
+        let searched = minimax::search(state, deadline);
+        Searches::decide(searched, &self.fallback, state, session, deadline)
+    }
+
+    fn end(&self, state: &State, session: &mut Session) {
+        session
+            .memory::<Searches>()
+            .report(self.name(), state, "depth");
+    }
+}
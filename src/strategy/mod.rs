@@ -0,0 +1,141 @@
+mod evaluation;
+mod explain;
+mod greedy;
+mod info;
+mod mcts;
+mod minimax;
+mod pipeline;
+mod trace;
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use clap::ArgEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Move, State};
+use crate::search::Deadline;
+use crate::session::Session;
+
+pub use evaluation::{Heuristic, Score, Weights};
+pub use explain::{Candidate, Explanation};
+pub use greedy::Greedy;
+pub use info::{Appearance, Color, Customization, Info};
+pub use mcts::Mcts;
+pub use minimax::Minimax;
+pub use pipeline::{safe_move, Filter, Pipeline};
+pub use trace::{DecisionTrace, Step};
+
+/// A way of playing the game. Each strategy is served under its own path prefix, so several
+/// snakes can run side by side from one server. Anything a strategy wants to remember from one
+/// turn to the next goes in the game's `Session`.
+///
+/// The server can't stop a strategy that runs over, and the session stays locked until it
+/// returns, holding up the game's next move. So anything slower than a few milliseconds should
+/// give up once `deadline` to `decide` has passed.
+pub trait Strategy: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn info(&self) -> Info {
+        Info::default()
+    }
+
+    fn start(&self, _state: &State, _session: &mut Session) {}
+
+    fn decide(&self, state: &State, session: &mut Session, deadline: Deadline) -> Result<Decision>;
+
+    fn end(&self, _state: &State, _session: &mut Session) {}
+}
+
+/// The move a strategy settled on, and why for those that keep track.
+#[derive(Debug, Clone)]
+pub struct Decision {
+    pub mv: Move,
+    pub shout: String,
+    pub trace: Option<DecisionTrace>,
+}
+
+impl Decision {
+    pub fn new(mv: Move) -> Decision {
+        Decision {
+            mv,
+            shout: "".to_string(),
+            trace: None,
+        }
+    }
+}
+
+/// How a search strategy has fared over a game, kept in its session.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Searches {
+    searches: usize,
+    /// Total depth or iterations reached.
+    reached: usize,
+    fallbacks: usize,
+}
+
+impl Searches {
+    /// Plays the move a search found, counting how far it got, or the fallback's if it found none.
+    fn decide(
+        searched: Option<(Move, usize)>,
+        fallback: &Greedy,
+        state: &State,
+        session: &mut Session,
+        deadline: Deadline,
+    ) -> Result<Decision> {
+        match searched {
+            Some((mv, reached)) => {
+                let searches = session.memory::<Searches>();
+                searches.searches += 1;
+                searches.reached += reached;
+
+                Ok(Decision::new(mv))
+            }
+            None => {
+                session.memory::<Searches>().fallbacks += 1;
+                fallback.decide(state, session, deadline)
+            }
+        }
+    }
+
+    fn report(&self, strategy: &str, state: &State, unit: &str) {
+        let average = self.reached.checked_div(self.searches).unwrap_or(0);
+
+        log::info!(
+            target: "search",
+            "game={} turn={} strategy={} searches={} average_{}={} fallbacks={}",
+            state.game.id,
+            state.turn,
+            strategy,
+            self.searches,
+            unit,
+            average,
+            self.fallbacks
+        );
+    }
+}
+
+/// The strategies we can build, for picking one from the command line or a config file.
+#[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StrategyKind {
+    Greedy,
+    Scored,
+    Minimax,
+    Mcts,
+}
+
+impl StrategyKind {
+    /// Builds the strategy, basing it or its fallback on `greedy`.
+    pub fn build(&self, greedy: Greedy) -> Arc<dyn Strategy> {
+        match self {
+            StrategyKind::Scored => Arc::new(Greedy::scored(
+                greedy.hunger_coefficient,
+                greedy.weights.clone(),
+            )),
+            StrategyKind::Greedy => Arc::new(greedy),
+            StrategyKind::Minimax => Arc::new(Minimax::new(greedy)),
+            StrategyKind::Mcts => Arc::new(Mcts::new(greedy)),
+        }
+    }
+}
@@ -0,0 +1,338 @@
+use std::fmt;
+use std::panic;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Instant;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::load_config;
+use crate::game::{Mode, Move, Point, State};
+
+use super::trace::{DecisionTrace, Step};
+
+/// A single named step of the greedy pipeline. Each one narrows the candidate moves down to those
+/// landing on a point it likes.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Filter {
+    InBounds,
+    SnakeCollisions,
+    Threatened,
+    Hazards,
+    LargestPocket,
+    Food,
+    Kill,
+    SeekKill,
+    Circle,
+}
+
+impl Filter {
+    pub fn all() -> Vec<Filter> {
+        vec![
+            Filter::InBounds,
+            Filter::SnakeCollisions,
+            Filter::Threatened,
+            Filter::Hazards,
+            Filter::LargestPocket,
+            Filter::Food,
+            Filter::Kill,
+            Filter::SeekKill,
+            Filter::Circle,
+        ]
+    }
+
+    /// The name used in config files and on the command line.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Filter::InBounds => "in_bounds",
+            Filter::SnakeCollisions => "snake_collisions",
+            Filter::Threatened => "threatened",
+            Filter::Hazards => "hazards",
+            Filter::LargestPocket => "largest_pocket",
+            Filter::Food => "food",
+            Filter::Kill => "kill",
+            Filter::SeekKill => "seek_kill",
+            Filter::Circle => "circle",
+        }
+    }
+
+    /// Narrows `moves` down, unless that would leave none, in which case the step is skipped.
+    pub fn apply(&self, state: &State, hunger_coefficient: f32, moves: Vec<Move>) -> Step {
+        let started = Instant::now();
+        let before = moves.clone();
+        let after = self.narrow(state, hunger_coefficient, moves);
+        let skipped = after.is_empty() && !before.is_empty();
+
+        Step {
+            filter: *self,
+            after: if skipped { before.clone() } else { after },
+            before,
+            skipped,
+            elapsed: started.elapsed(),
+        }
+    }
+
+    fn narrow(&self, state: &State, hunger_coefficient: f32, moves: Vec<Move>) -> Vec<Move> {
+        match self {
+            Filter::InBounds => process(state, moves, |point| state.board.in_bounds(&point)),
+            Filter::SnakeCollisions => {
+                // Constrictor snakes grow every turn, so their tails never move out of the way.
+                let tails_move = state.game.ruleset.mode() != Mode::Constrictor;
+                let squad_bodies = state.game.ruleset.squad_body_collisions();
+
+                process(state, moves, |point| {
+                    !state.board.snakes.iter().any(|snake| {
+                        !(squad_bodies && snake.squadmate(&state.you))
+                            && snake.at(
+                                &point,
+                                tails_move && (snake != &state.you || state.turn > 2),
+                            )
+                    })
+                })
+            }
+            Filter::Threatened => process(state, moves, |point| !state.threatened(&point)),
+            Filter::Hazards => process(state, moves, |point| !state.board.hazard_at(&point)),
+            Filter::LargestPocket => {
+                let pocket_sizes = state.board.pocket_sizes();
+                let largest = moves
+                    .iter()
+                    .map(|mv| {
+                        pocket_sizes
+                            .get(&state.board.shift(&state.you.head, mv))
+                            .unwrap_or(&0usize)
+                    })
+                    .max()
+                    .copied();
+
+                match largest {
+                    Some(size) => process(state, moves, |point| {
+                        *pocket_sizes.get(&point).unwrap_or(&0usize) == size
+                    }),
+                    None => moves,
+                }
+            }
+            Filter::Food => match state.board.closest_food(&state.you.head) {
+                Some(closest_food) => {
+                    let distance = closest_food.distance(&state.you.head);
+
+                    if state.need_food(distance, hunger_coefficient) || state.compete_for_biggest()
+                    {
+                        process(state, moves, |point| {
+                            closest_food.distance(&point) < distance
+                        })
+                    } else {
+                        moves
+                    }
+                }
+                None => moves,
+            },
+            Filter::Kill => process(state, moves, |point| state.kill_chance(&point)),
+            Filter::SeekKill => {
+                let closest_smaller_snake = state
+                    .board
+                    .snakes
+                    .iter()
+                    .filter(|snake| snake.length() < state.you.length())
+                    .min_by(|snake_a, snake_b| {
+                        let distance_a = state.you.head.distance(&snake_a.head);
+                        let distance_b = state.you.head.distance(&snake_b.head);
+
+                        distance_a.cmp(&distance_b)
+                    });
+
+                match closest_smaller_snake {
+                    Some(snake) => process(state, moves, |point| {
+                        let current_distance = state.you.head.distance(&snake.head);
+                        let new_distance = point.distance(&snake.head);
+
+                        new_distance < current_distance
+                    }),
+                    None => moves,
+                }
+            }
+            Filter::Circle => process(state, moves, |point| {
+                let tail = state.you.tail();
+                let current_distance = state.you.head.distance(tail);
+                let new_distance = point.distance(tail);
+
+                new_distance < current_distance
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.key())
+    }
+}
+
+impl FromStr for Filter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Filter::all()
+            .into_iter()
+            .find(|filter| filter.key() == s.trim())
+            .ok_or_else(|| anyhow!("unknown filter '{}'", s))
+    }
+}
+
+/// An ordered list of filters, loadable from a TOML or JSON file, or a comma separated list.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Pipeline {
+    pub filters: Vec<Filter>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Pipeline {
+            filters: Filter::all(),
+        }
+    }
+}
+
+impl Pipeline {
+    /// Just the filters that rule out moves which are certainly or very likely fatal.
+    pub fn safety() -> Pipeline {
+        Pipeline {
+            filters: vec![Filter::InBounds, Filter::SnakeCollisions, Filter::Hazards],
+        }
+    }
+
+    /// What's run when a strategy fails to decide in time: just enough to stay alive this turn
+    /// and keep room to move, while being quick.
+    pub fn fallback() -> Pipeline {
+        Pipeline {
+            filters: vec![
+                Filter::InBounds,
+                Filter::SnakeCollisions,
+                Filter::Threatened,
+                Filter::Hazards,
+                Filter::LargestPocket,
+            ],
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Pipeline> {
+        load_config(path, "pipeline")
+    }
+
+    /// Runs every filter in turn, recording each step in `trace`, and returns the moves left.
+    pub fn run(
+        &self,
+        state: &State,
+        hunger_coefficient: f32,
+        trace: &mut DecisionTrace,
+    ) -> Vec<Move> {
+        self.filters.iter().fold(Move::all(), |moves, filter| {
+            let step = filter.apply(state, hunger_coefficient, moves);
+            let moves = step.after.clone();
+            trace.steps.push(step);
+            moves
+        })
+    }
+}
+
+/// The move to play when the strategy can't give one. Keeps going the same way when that's as
+/// good as any other, or when the state is too broken for the filters, say a snake with no body.
+pub fn safe_move(state: &State) -> Move {
+    let direction = state.you.direction();
+    let moves = match panic::catch_unwind(|| {
+        Pipeline::fallback().run(state, 0.0, &mut DecisionTrace::new(state))
+    }) {
+        Ok(moves) => moves,
+        Err(_) => return direction,
+    };
+
+    if moves.contains(&direction) {
+        direction
+    } else {
+        moves.first().copied().unwrap_or(direction)
+    }
+}
+
+impl FromStr for Pipeline {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let filters = s
+            .split(',')
+            .filter(|key| !key.trim().is_empty())
+            .map(Filter::from_str)
+            .collect::<Result<Vec<Filter>>>()?;
+
+        Ok(Pipeline { filters })
+    }
+}
+
+fn process<F>(state: &State, moves: Vec<Move>, f: F) -> Vec<Move>
+where
+    F: Fn(Point) -> bool,
+{
+    moves
+        .into_iter()
+        .filter(|mv| f(state.board.shift(&state.you.head, mv)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            "in_bounds, hazards,circle"
+                .parse::<Pipeline>()
+                .expect("must parse"),
+            Pipeline {
+                filters: vec![Filter::InBounds, Filter::Hazards, Filter::Circle]
+            }
+        );
+
+        assert!("in_bounds,teleport".parse::<Pipeline>().is_err());
+    }
+
+    #[test]
+    fn deserialize() {
+        let from_toml: Pipeline =
+            toml::from_str(r#"filters = ["snake_collisions", "seek_kill"]"#).expect("must parse");
+        let from_json: Pipeline =
+            serde_json::from_str(r#"{"filters": ["snake_collisions", "seek_kill"]}"#)
+                .expect("must parse");
+
+        assert_eq!(from_toml, from_json);
+        assert_eq!(
+            from_toml.filters,
+            vec![Filter::SnakeCollisions, Filter::SeekKill]
+        );
+    }
+
+    #[test]
+    fn safe_move() {
+        let state = |picture: &str| picture.parse::<State>().expect("must parse");
+
+        // Heading up into the wall, with a body on the left.
+        assert_eq!(
+            super::safe_move(&state(
+                "
+                b b A .
+                . B a .
+                . . a .
+                "
+            )),
+            Move::Right
+        );
+
+        assert_eq!(super::safe_move(&state(". .\n. .\nA a")), Move::Up);
+        assert_eq!(super::safe_move(&state(". A a .")), Move::Left);
+
+        let mut bodiless = state("turn: 5\n. A a .");
+        bodiless.you.body.clear();
+        bodiless.board.snakes[0].body.clear();
+        assert_eq!(super::safe_move(&bodiless), Move::Up);
+    }
+}
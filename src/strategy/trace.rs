@@ -0,0 +1,163 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Move, Renderer, State};
+
+use super::evaluation::Score;
+use super::pipeline::Filter;
+
+/// What one filter made of the candidate moves.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Step {
+    pub filter: Filter,
+    pub before: Vec<Move>,
+    pub after: Vec<Move>,
+    /// Set when the filter would have ruled out every move, so was ignored.
+    pub skipped: bool,
+    #[serde(rename = "elapsed_us", with = "micros")]
+    pub elapsed: Duration,
+}
+
+/// Why a move was chosen: each step of the pipeline, the scores of the moves it left, and the
+/// pick.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DecisionTrace {
+    pub game: String,
+    pub turn: u16,
+    pub steps: Vec<Step>,
+    pub scores: Vec<Score>,
+    pub chosen: Option<Move>,
+    #[serde(rename = "elapsed_us", with = "micros")]
+    pub elapsed: Duration,
+}
+
+impl DecisionTrace {
+    pub fn new(state: &State) -> DecisionTrace {
+        DecisionTrace {
+            game: state.game.id.clone(),
+            turn: state.turn,
+            steps: vec![],
+            scores: vec![],
+            chosen: None,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// The step that ruled `mv` out, if one did.
+    pub fn ruled_out_by(&self, mv: Move) -> Option<&Step> {
+        self.steps
+            .iter()
+            .find(|step| step.before.contains(&mv) && !step.after.contains(&mv))
+    }
+
+    /// The moves left once every filter had its say.
+    pub fn candidates(&self) -> Vec<Move> {
+        self.steps
+            .last()
+            .map(|step| step.after.clone())
+            .unwrap_or_default()
+    }
+
+    /// Logs every step at debug level and the pick at info, each tagged with the game and turn,
+    /// and at trace level the board with the candidates marked.
+    pub fn log(&self, state: &State) {
+        if log::log_enabled!(target: "decision", log::Level::Trace) {
+            let renderer = Renderer {
+                candidates: self.candidates(),
+                chosen: self.chosen,
+                ..Renderer::plain()
+            };
+            log::trace!(
+                target: "decision",
+                "game={} turn={}\n{}",
+                self.game,
+                self.turn,
+                renderer.render_state(state)
+            );
+        }
+
+        for step in self.steps.iter() {
+            log::debug!(
+                target: "decision",
+                "game={} turn={} filter={} before={:?} after={:?} skipped={} elapsed_us={}",
+                self.game,
+                self.turn,
+                step.filter,
+                step.before,
+                step.after,
+                step.skipped,
+                step.elapsed.as_micros()
+            );
+        }
+
+        log::info!(
+            target: "decision",
+            "game={} turn={} chosen={:?} scores={:?} elapsed_us={}",
+            self.game,
+            self.turn,
+            self.chosen,
+            self.scores
+                .iter()
+                .map(|score| (score.mv, score.total))
+                .collect::<Vec<_>>(),
+            self.elapsed.as_micros()
+        );
+    }
+}
+
+/// Durations as whole microseconds, which is plenty for timing a decision.
+mod micros {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_micros() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_micros)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::strategy::{Greedy, Pipeline, Weights};
+
+    use super::*;
+
+    #[test]
+    fn why() {
+        let state: State = "
+            . . $ .
+            . . . .
+            . . . .
+            A a a .
+        "
+        .parse()
+        .expect("must parse");
+
+        let greedy = Greedy::new(1.5, Pipeline::default(), Weights::default());
+        let trace = greedy.decide_traced(&state);
+
+        assert_eq!(trace.game, "test");
+        assert_eq!(trace.steps.len(), Pipeline::default().filters.len());
+        assert_eq!(trace.chosen, Some(Move::Up));
+        assert_eq!(
+            trace.ruled_out_by(Move::Down).map(|step| step.filter),
+            Some(Filter::InBounds)
+        );
+        assert_eq!(
+            trace.ruled_out_by(Move::Right).map(|step| step.filter),
+            Some(Filter::SnakeCollisions)
+        );
+        assert_eq!(trace.ruled_out_by(Move::Up), None);
+
+        // Up leads away from the tail, so circling would leave no moves and is skipped.
+        let circle = trace.steps.last().expect("must have steps");
+        assert_eq!(circle.filter, Filter::Circle);
+        assert!(circle.skipped);
+        assert_eq!(circle.after, vec![Move::Up]);
+    }
+}
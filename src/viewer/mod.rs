@@ -0,0 +1,120 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Move, State};
+use crate::record::Entry;
+use crate::strategy::{Decision, DecisionTrace};
+
+/// The viewer's page, a single file with its styles and script inlined.
+pub const PAGE: &str = include_str!("index.html");
+
+/// One turn of a game as the viewer shows it.
+#[derive(Serialize, Debug, Clone)]
+pub struct Frame {
+    pub turn: u16,
+    pub state: State,
+    /// What the strategy chose, nothing on the final turn or if it failed to decide.
+    #[serde(rename = "move")]
+    pub mv: Option<Move>,
+    pub shout: String,
+    pub trace: Option<DecisionTrace>,
+}
+
+impl Frame {
+    pub fn new(state: &State, decision: Option<&Decision>) -> Frame {
+        Frame {
+            turn: state.turn,
+            state: state.clone(),
+            mv: decision.map(|decision| decision.mv),
+            shout: decision
+                .map(|decision| decision.shout.clone())
+                .unwrap_or_default(),
+            trace: decision.and_then(|decision| decision.trace.clone()),
+        }
+    }
+}
+
+/// A frame for every move of a recorded game, then one for how it ended.
+pub fn frames(entries: &[Entry]) -> Result<Vec<Frame>> {
+    let mut frames: Vec<Frame> = Vec::new();
+
+    for entry in entries {
+        let state = State::deserialize(entry.request()).context("bad recorded request")?;
+
+        match entry {
+            Entry::Start { .. } => {}
+            Entry::Move {
+                mv, shout, trace, ..
+            } => frames.push(Frame::new(
+                &state,
+                Some(&Decision {
+                    mv: *mv,
+                    shout: shout.clone(),
+                    trace: trace.clone(),
+                }),
+            )),
+            Entry::End { .. } => frames.push(Frame::new(&state, None)),
+        }
+    }
+
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn recorded() {
+        let request = |turn: u16| {
+            let state: State = format!(
+                "
+                turn: {}
+                ruleset: solo
+
+                . . .
+                . A .
+                . a .
+
+                A: id=you health=90
+                ",
+                turn
+            )
+            .parse()
+            .expect("must parse");
+
+            serde_json::to_value(&state).expect("must serialize")
+        };
+
+        let entries = vec![
+            Entry::Start {
+                request: request(0),
+            },
+            Entry::Move {
+                request: request(0),
+                strategy: "greedy".to_string(),
+                mv: Move::Up,
+                shout: "hi".to_string(),
+                trace: None,
+            },
+            Entry::End {
+                request: request(1),
+            },
+        ];
+
+        let frames = frames(&entries).expect("must read");
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].mv, Some(Move::Up));
+        assert_eq!(frames[0].shout, "hi");
+        assert_eq!(frames[1].turn, 1);
+        assert_eq!(frames[1].mv, None);
+
+        assert!(super::frames(&[Entry::End {
+            request: json!({"turn": 1})
+        }])
+        .is_err());
+    }
+}